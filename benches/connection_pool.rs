@@ -0,0 +1,101 @@
+// Compares lookups through one long-lived TorusClient against a fresh client per lookup, using
+// a local plain http stand-in for the torus nodes. A fresh client opens new connections and also
+// builds a new reqwest client and TLS config, which is most of what it costs here. No TLS
+// handshake is made.
+//
+//   cargo bench --bench connection_pool
+
+use hyper::{
+    server::conn::AddrStream,
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server,
+};
+use partisia_torus::{TorusClient, VerifierId};
+use serde_json::Value;
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+const NODES: usize = 5;
+const LOOKUPS: usize = 200;
+
+const VERIFIER_LOOKUP_RESPONSE: &str = r#"{
+  "jsonrpc": "2.0",
+  "result": {
+    "keys": [
+      {
+        "key_index": "14745a",
+        "pub_key_X": "436676f1c06a11f805a92d5d02a5789296c562d1aeb8e72d6318760f61cdcbf",
+        "pub_key_Y": "afd563755d627d1ae4021d60863acca0c3bf4e5d8f5ce24c91e55ebbf5b263b0",
+        "address": "0xC9F0af3d1D6089992C0041902D846c4b448311F2"
+      }
+    ]
+  },
+  "id": 10
+}"#;
+
+// serves the same answer on every path, under the id of the request, and counts the tcp
+// connections it accepts
+async fn start_stand_in(connections: Arc<AtomicUsize>) -> SocketAddr {
+    let make_svc = make_service_fn(move |_: &AddrStream| {
+        connections.fetch_add(1, Ordering::Relaxed);
+        async {
+            Ok::<_, Infallible>(service_fn(|req: Request<Body>| async {
+                let body = hyper::body::to_bytes(req.into_body()).await.unwrap();
+                let request: Value = serde_json::from_slice(&body).unwrap();
+                let mut response: Value = serde_json::from_str(VERIFIER_LOOKUP_RESPONSE).unwrap();
+                response["id"] = request["id"].clone();
+                Ok::<_, Infallible>(Response::new(Body::from(response.to_string())))
+            }))
+        }
+    });
+    let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_svc);
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    addr
+}
+
+async fn run(name: &str, endpoints: &[String], shared: bool) {
+    let connections = Arc::new(AtomicUsize::new(0));
+    let addr = start_stand_in(Arc::clone(&connections)).await;
+    let endpoints: Vec<String> = endpoints
+        .iter()
+        .map(|path| format!("http://{}{}", addr, path))
+        .collect();
+
+    let client = TorusClient::new(endpoints.clone());
+    let start = Instant::now();
+    for _ in 0..LOOKUPS {
+        let key = if shared {
+            client
+                .lookup_request(&VerifierId::twitter(1415723267256639488))
+                .await
+        } else {
+            TorusClient::new(endpoints.clone())
+                .lookup_request(&VerifierId::twitter(1415723267256639488))
+                .await
+        };
+        assert!(key.unwrap().is_some());
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{:<14} {:>8.0} lookups/s  {:>5} connections  {:?} total",
+        name,
+        LOOKUPS as f64 / elapsed.as_secs_f64(),
+        connections.load(Ordering::Relaxed),
+        elapsed
+    );
+}
+
+#[tokio::main]
+async fn main() {
+    let endpoints: Vec<String> = (1..=NODES).map(|i| format!("/node-{}/jrpc", i)).collect();
+    run("fresh client", &endpoints, false).await;
+    run("shared client", &endpoints, true).await;
+}
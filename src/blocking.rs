@@ -0,0 +1,207 @@
+// Synchronous lookups for code without an async runtime. They run the same consensus as the async
+// lookups, calling the nodes concurrently on a runtime owned by this module, so they must not be
+// called from within an async context.
+
+use crate::{
+    Consensus, ConsensusPolicy, FormatLookup, KeyLookup, Result, TorusMethod, TorusNetwork,
+    Verifier, VerifierId, VerifierKeys,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::{future::Future, sync::OnceLock};
+
+#[cfg(feature = "tokio")]
+fn block_on<F: Future>(future: F) -> F::Output {
+    static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+    RUNTIME
+        .get_or_init(|| {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("failed to build the blocking runtime")
+        })
+        .block_on(future)
+}
+
+#[cfg(not(feature = "tokio"))]
+fn block_on<F: Future>(future: F) -> F::Output {
+    futures::executor::block_on(future)
+}
+
+/// A `crate::TorusClient` with synchronous lookups
+#[derive(Debug, Clone, Default)]
+pub struct TorusClient {
+    inner: crate::TorusClient,
+}
+
+impl TorusClient {
+    pub fn with_network(network: TorusNetwork) -> Self {
+        crate::TorusClient::with_network(network).into()
+    }
+
+    pub fn inner(&self) -> &crate::TorusClient {
+        &self.inner
+    }
+
+    pub fn lookup_request(&self, verifier_id: &VerifierId) -> Result<Option<[u8; 65]>> {
+        block_on(self.inner.lookup_request(verifier_id))
+    }
+    pub fn lookup_request_with_policy(
+        &self,
+        verifier_id: &VerifierId,
+        policy: ConsensusPolicy,
+    ) -> Result<Consensus<Option<[u8; 65]>>> {
+        block_on(self.inner.lookup_request_with_policy(verifier_id, policy))
+    }
+    pub fn lookup_keys_request(&self, verifier_id: &VerifierId) -> Result<VerifierKeys> {
+        block_on(self.inner.lookup_keys_request(verifier_id))
+    }
+    pub fn lookup_keys_request_with_policy(
+        &self,
+        verifier_id: &VerifierId,
+        policy: ConsensusPolicy,
+    ) -> Result<Consensus<VerifierKeys>> {
+        block_on(
+            self.inner
+                .lookup_keys_request_with_policy(verifier_id, policy),
+        )
+    }
+    pub fn lookup_request_any_format(
+        &self,
+        verifier_id: &VerifierId,
+        policy: ConsensusPolicy,
+    ) -> Result<FormatLookup> {
+        block_on(self.inner.lookup_request_any_format(verifier_id, policy))
+    }
+    pub fn key_lookup_request(
+        &self,
+        pub_key_x: &[u8; 32],
+        pub_key_y: &[u8; 32],
+    ) -> Result<KeyLookup> {
+        block_on(self.inner.key_lookup_request(pub_key_x, pub_key_y))
+    }
+    pub fn key_lookup_request_with_policy(
+        &self,
+        pub_key_x: &[u8; 32],
+        pub_key_y: &[u8; 32],
+        policy: ConsensusPolicy,
+    ) -> Result<Consensus<KeyLookup>> {
+        block_on(
+            self.inner
+                .key_lookup_request_with_policy(pub_key_x, pub_key_y, policy),
+        )
+    }
+    pub fn key_lookup_request_for(
+        &self,
+        pub_key_x: &[u8; 32],
+        pub_key_y: &[u8; 32],
+        verifier_type: Verifier,
+    ) -> Result<Option<VerifierId>> {
+        block_on(
+            self.inner
+                .key_lookup_request_for(pub_key_x, pub_key_y, verifier_type),
+        )
+    }
+    pub fn key_lookup_request_for_with_policy(
+        &self,
+        pub_key_x: &[u8; 32],
+        pub_key_y: &[u8; 32],
+        verifier_type: Verifier,
+        policy: ConsensusPolicy,
+    ) -> Result<Consensus<Option<VerifierId>>> {
+        block_on(self.inner.key_lookup_request_for_with_policy(
+            pub_key_x,
+            pub_key_y,
+            verifier_type,
+            policy,
+        ))
+    }
+    pub fn call<M: TorusMethod>(&self, params: M::Params) -> Result<M::Response> {
+        block_on(self.inner.call::<M>(params))
+    }
+    pub fn call_with_policy<M: TorusMethod>(
+        &self,
+        params: M::Params,
+        policy: ConsensusPolicy,
+    ) -> Result<Consensus<M::Response>> {
+        block_on(self.inner.call_with_policy::<M>(params, policy))
+    }
+    pub fn request_with_consensus<T, K>(
+        &self,
+        method: &str,
+        params: Value,
+        policy: ConsensusPolicy,
+        key: impl Fn(&T) -> Result<K> + Sync,
+    ) -> Result<Consensus<T>>
+    where
+        T: DeserializeOwned,
+        K: Serialize,
+    {
+        block_on(
+            self.inner
+                .request_with_consensus(method, params, policy, key),
+        )
+    }
+}
+
+impl From<crate::TorusClient> for TorusClient {
+    fn from(inner: crate::TorusClient) -> Self {
+        Self { inner }
+    }
+}
+
+// separate from the async default client, whose connections may belong to another runtime
+fn default_client() -> &'static TorusClient {
+    static DEFAULT_CLIENT: OnceLock<TorusClient> = OnceLock::new();
+    DEFAULT_CLIENT.get_or_init(TorusClient::default)
+}
+
+pub fn lookup_request(verifier_id: &VerifierId) -> Result<Option<[u8; 65]>> {
+    default_client().lookup_request(verifier_id)
+}
+pub fn lookup_request_with_policy(
+    verifier_id: &VerifierId,
+    policy: ConsensusPolicy,
+) -> Result<Consensus<Option<[u8; 65]>>> {
+    default_client().lookup_request_with_policy(verifier_id, policy)
+}
+pub fn lookup_keys_request(verifier_id: &VerifierId) -> Result<VerifierKeys> {
+    default_client().lookup_keys_request(verifier_id)
+}
+pub fn lookup_keys_request_with_policy(
+    verifier_id: &VerifierId,
+    policy: ConsensusPolicy,
+) -> Result<Consensus<VerifierKeys>> {
+    default_client().lookup_keys_request_with_policy(verifier_id, policy)
+}
+pub fn lookup_request_any_format(
+    verifier_id: &VerifierId,
+    policy: ConsensusPolicy,
+) -> Result<FormatLookup> {
+    default_client().lookup_request_any_format(verifier_id, policy)
+}
+pub fn key_lookup_request(pub_key_x: &[u8; 32], pub_key_y: &[u8; 32]) -> Result<KeyLookup> {
+    default_client().key_lookup_request(pub_key_x, pub_key_y)
+}
+pub fn key_lookup_request_with_policy(
+    pub_key_x: &[u8; 32],
+    pub_key_y: &[u8; 32],
+    policy: ConsensusPolicy,
+) -> Result<Consensus<KeyLookup>> {
+    default_client().key_lookup_request_with_policy(pub_key_x, pub_key_y, policy)
+}
+pub fn key_lookup_request_for(
+    pub_key_x: &[u8; 32],
+    pub_key_y: &[u8; 32],
+    verifier_type: Verifier,
+) -> Result<Option<VerifierId>> {
+    default_client().key_lookup_request_for(pub_key_x, pub_key_y, verifier_type)
+}
+pub fn key_lookup_request_for_with_policy(
+    pub_key_x: &[u8; 32],
+    pub_key_y: &[u8; 32],
+    verifier_type: Verifier,
+    policy: ConsensusPolicy,
+) -> Result<Consensus<Option<VerifierId>>> {
+    default_client().key_lookup_request_for_with_policy(pub_key_x, pub_key_y, verifier_type, policy)
+}
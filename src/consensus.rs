@@ -0,0 +1,365 @@
+use crate::{sha256_hash, ConsensusGroup, ConsensusResults, NodeFailure, Result, TorusError};
+use futures::{Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::time::Duration;
+
+/// How many nodes have to return the same result before it is accepted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConsensusPolicy {
+    /// the threshold configured for the client's `TorusNetwork`
+    #[default]
+    Network,
+    /// more than half of the nodes
+    Majority,
+    /// at least k of the nodes
+    KOfN(usize),
+    /// every node
+    Unanimous,
+}
+
+impl ConsensusPolicy {
+    /// The number of agreeing nodes needed out of `nodes`
+    pub fn required(&self, network_threshold: usize, nodes: usize) -> Result<usize> {
+        if nodes == 0 {
+            return Err(TorusError::NoEndpoints);
+        }
+        let required = match self {
+            ConsensusPolicy::Network => network_threshold,
+            ConsensusPolicy::Majority => nodes / 2 + 1,
+            ConsensusPolicy::KOfN(k) => *k,
+            ConsensusPolicy::Unanimous => nodes,
+        };
+        if required == 0 || required > nodes {
+            return Err(TorusError::InvalidThreshold { required, nodes });
+        }
+        Ok(required)
+    }
+}
+
+/// A value the nodes agreed on, along with the standard that was applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Consensus<T> {
+    pub value: T,
+    pub policy: ConsensusPolicy,
+    /// the number of nodes that had to agree under `policy`
+    pub required: usize,
+}
+
+impl<T> Consensus<T> {
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Consensus<U> {
+        Consensus {
+            value: f(self.value),
+            policy: self.policy,
+            required: self.required,
+        }
+    }
+
+    pub fn try_map<U>(self, f: impl FnOnce(T) -> Result<U>) -> Result<Consensus<U>> {
+        Ok(Consensus {
+            value: f(self.value)?,
+            policy: self.policy,
+            required: self.required,
+        })
+    }
+}
+
+// a node's answer and how long the node took to give it
+pub(crate) struct NodeAnswer {
+    pub result: Result<NodeValue>,
+    pub latency: Duration,
+    pub attempts: u32,
+}
+
+// what a node returned, and the canonical form it is compared with other nodes on
+pub(crate) struct NodeValue {
+    pub key: Vec<u8>,
+    pub value: Value,
+}
+
+struct FoldGroups<'a> {
+    key: [u8; 32],
+    result: &'a Value,
+    nodes: Vec<usize>,
+}
+
+// the state of a consensus call after the latest node has answered
+pub(crate) enum Tally<'a> {
+    // enough nodes returned this result
+    Agreed(&'a Value),
+    // no result can reach the threshold any more, even if every pending node agrees with it
+    Impossible(TorusError),
+    Pending,
+}
+
+pub(crate) fn tally(results: &ConsensusResults, consensus_num: usize) -> Tally<'_> {
+    let pending = results.iter().filter(|r| r.is_none()).count();
+
+    // group the matches and record which nodes returned them using sha256 hash
+    let mut res_grouped = Vec::new();
+    let mut errors = Vec::new();
+    for (idx, res) in results.iter().enumerate() {
+        match res.as_ref().map(|answer| &answer.result) {
+            Some(Ok(answer)) => {
+                let hash_key = sha256_hash(&answer.key);
+                match res_grouped
+                    .iter()
+                    .position(|g: &FoldGroups| g.key == hash_key)
+                {
+                    Some(g) => res_grouped[g].nodes.push(idx),
+                    None => res_grouped.push(FoldGroups {
+                        key: hash_key,
+                        result: &answer.value,
+                        nodes: vec![idx],
+                    }),
+                }
+            }
+            Some(Err(e)) => errors.push(NodeFailure {
+                node: idx,
+                error: e.clone(),
+            }),
+            None => {}
+        }
+    }
+    // sort result by count desc
+    res_grouped.sort_by_key(|g| std::cmp::Reverse(g.nodes.len()));
+
+    // check consensus threshold is met by the group with the highest count
+    if let Some(group) = res_grouped.first() {
+        if group.nodes.len() >= consensus_num {
+            return Tally::Agreed(group.result);
+        }
+    }
+
+    // nodes agreeing that nothing is assigned decide the call just like nodes agreeing on a result
+    let not_found = errors
+        .iter()
+        .filter(|f| f.error == TorusError::KeyNotFound)
+        .count();
+    if not_found >= consensus_num {
+        return Tally::Impossible(TorusError::KeyNotFound);
+    }
+
+    // a group can still win if the pending nodes join it, or a new group can form from them alone
+    // or with the nodes that found nothing
+    let possible = not_found + pending >= consensus_num
+        || res_grouped
+            .iter()
+            .any(|g| g.nodes.len() + pending >= consensus_num);
+    if possible {
+        return Tally::Pending;
+    }
+
+    // while nodes are pending it is no consensus, not every node failing
+    if res_grouped.is_empty() && pending == 0 {
+        return Tally::Impossible(TorusError::AllNodesFailed(errors));
+    }
+    Tally::Impossible(TorusError::NoConsensus {
+        required: consensus_num,
+        nodes: results.len(),
+        groups: res_grouped
+            .into_iter()
+            .map(|g| ConsensusGroup {
+                nodes: g.nodes,
+                hash: g.key,
+            })
+            .collect(),
+        errors,
+    })
+}
+
+// the first node to see consensus, or to see that it can no longer be reached, decides the call
+pub(crate) async fn first_decision<T>(
+    mut votes: impl Stream<Item = Result<Option<T>>> + Unpin,
+) -> Result<T> {
+    while let Some(vote) = votes.next().await {
+        if let Some(value) = vote? {
+            return Ok(value);
+        }
+    }
+    unreachable!("tally decides the call once every node has answered")
+}
+
+pub(crate) fn deadline_exceeded(
+    results: &ConsensusResults,
+    endpoints: &[String],
+    deadline: Duration,
+) -> TorusError {
+    TorusError::DeadlineExceeded {
+        deadline,
+        pending: results
+            .iter()
+            .zip(endpoints)
+            .filter(|(res, _)| res.is_none())
+            .map(|(_, endpoint)| endpoint.clone())
+            .collect(),
+    }
+}
+
+/// What each node contributed to a consensus call
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ConsensusReport {
+    pub required: usize,
+    pub nodes: Vec<NodeReport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeReport {
+    pub endpoint: String,
+    /// None if the node had not answered when the call was decided
+    pub latency: Option<Duration>,
+    /// how many times the node was called, None if it had not answered
+    pub attempts: Option<u32>,
+    pub outcome: NodeOutcome,
+    /// nodes with the same group returned the same result, numbered in endpoint order
+    pub group: Option<usize>,
+    /// sha256 of the canonical form of the node's result
+    pub hash: Option<[u8; 32]>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeOutcome {
+    /// the node returned the accepted result, or agreed that nothing is assigned
+    Agreed,
+    /// the node returned a result that was not accepted
+    Disagreed,
+    Error(TorusError),
+    /// the call was decided, or the lookup deadline passed, before the node answered
+    Cancelled,
+}
+
+pub(crate) fn report(
+    results: &ConsensusResults,
+    endpoints: &[String],
+    consensus_num: usize,
+) -> ConsensusReport {
+    let hashes: Vec<Option<[u8; 32]>> = results
+        .iter()
+        .map(|res| match res.as_ref().map(|answer| &answer.result) {
+            Some(Ok(answer)) => Some(sha256_hash(&answer.key)),
+            _ => None,
+        })
+        .collect();
+    let mut groups: Vec<[u8; 32]> = Vec::new();
+    for hash in hashes.iter().flatten() {
+        if !groups.contains(hash) {
+            groups.push(*hash);
+        }
+    }
+    let accepted = groups
+        .iter()
+        .find(|g| hashes.iter().filter(|h| h.as_ref() == Some(*g)).count() >= consensus_num);
+    let not_found_agreed =
+        accepted.is_none() && results.iter().filter(|res| not_found(res)).count() >= consensus_num;
+
+    let nodes = results
+        .iter()
+        .zip(endpoints)
+        .zip(hashes)
+        .map(|((res, endpoint), hash)| {
+            let outcome = match res.as_ref().map(|answer| &answer.result) {
+                None => NodeOutcome::Cancelled,
+                Some(Err(TorusError::KeyNotFound)) if not_found_agreed => NodeOutcome::Agreed,
+                Some(Err(e)) => NodeOutcome::Error(e.clone()),
+                Some(Ok(_)) if hash.as_ref() == accepted => NodeOutcome::Agreed,
+                Some(Ok(_)) => NodeOutcome::Disagreed,
+            };
+            NodeReport {
+                endpoint: endpoint.clone(),
+                latency: res.as_ref().map(|answer| answer.latency),
+                attempts: res.as_ref().map(|answer| answer.attempts),
+                outcome,
+                group: hash.and_then(|h| groups.iter().position(|g| *g == h)),
+                hash,
+            }
+        })
+        .collect();
+    ConsensusReport {
+        required: consensus_num,
+        nodes,
+    }
+}
+
+fn not_found(res: &Option<NodeAnswer>) -> bool {
+    matches!(
+        res.as_ref().map(|answer| &answer.result),
+        Some(Err(TorusError::KeyNotFound))
+    )
+}
+
+/// The outcome of querying every node to completion
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Audit<T> {
+    /// the result returned by at least `required` nodes, if any
+    pub value: Option<T>,
+    pub policy: ConsensusPolicy,
+    pub required: usize,
+    /// `agreement[i][j]` is true if nodes i and j returned the same result
+    pub agreement: Vec<Vec<bool>>,
+    pub report: ConsensusReport,
+}
+
+impl<T> Audit<T> {
+    /// Nodes that failed or returned anything other than the accepted result
+    pub fn dissenting_nodes(&self) -> Vec<usize> {
+        self.report
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| n.outcome != NodeOutcome::Agreed)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    pub fn try_map<U>(self, f: impl FnOnce(T) -> Result<U>) -> Result<Audit<U>> {
+        Ok(Audit {
+            value: self.value.map(f).transpose()?,
+            policy: self.policy,
+            required: self.required,
+            agreement: self.agreement,
+            report: self.report,
+        })
+    }
+}
+
+// the value is Some(None) when enough nodes agree that nothing is assigned, as with
+// `TorusClient::found`
+pub(crate) fn audit<T>(
+    results: &ConsensusResults,
+    endpoints: &[String],
+    consensus_num: usize,
+    policy: ConsensusPolicy,
+) -> Result<Audit<Option<T>>>
+where
+    T: DeserializeOwned,
+{
+    let value = match tally(results, consensus_num) {
+        Tally::Agreed(value) => Some(Some(T::deserialize(value)?)),
+        Tally::Impossible(TorusError::KeyNotFound) => Some(None),
+        _ => None,
+    };
+    let report = report(results, endpoints, consensus_num);
+    let agreement = report
+        .nodes
+        .iter()
+        .zip(results)
+        .map(|(a, res_a)| {
+            report
+                .nodes
+                .iter()
+                .zip(results)
+                .map(|(b, res_b)| {
+                    (a.group.is_some() && a.group == b.group)
+                        || (not_found(res_a) && not_found(res_b))
+                })
+                .collect()
+        })
+        .collect();
+    Ok(Audit {
+        value,
+        policy,
+        required: consensus_num,
+        agreement,
+        report,
+    })
+}
@@ -0,0 +1,212 @@
+use super::*;
+use futures::StreamExt;
+use serde::de::DeserializeOwned;
+
+// The slots the node calls of one lookup write their answers into. The lock is never held across
+// an await, so the only difference between implementations is whether the lookup future is Send.
+pub(crate) trait SharedResults: Clone {
+    fn new(results: ConsensusResults) -> Self;
+    fn set(&self, idx: usize, answer: NodeAnswer);
+    fn read<R>(&self, f: impl FnOnce(&ConsensusResults) -> R) -> R;
+}
+
+impl SharedResults for Rc<RefCell<ConsensusResults>> {
+    fn new(results: ConsensusResults) -> Self {
+        Rc::new(RefCell::new(results))
+    }
+    fn set(&self, idx: usize, answer: NodeAnswer) {
+        self.borrow_mut()[idx] = Some(answer);
+    }
+    fn read<R>(&self, f: impl FnOnce(&ConsensusResults) -> R) -> R {
+        f(&self.borrow())
+    }
+}
+
+impl SharedResults for Arc<RwLock<ConsensusResults>> {
+    fn new(results: ConsensusResults) -> Self {
+        Arc::new(RwLock::new(results))
+    }
+    fn set(&self, idx: usize, answer: NodeAnswer) {
+        // a poisoned lock still holds the answers written before the panic
+        self.write().unwrap_or_else(PoisonError::into_inner)[idx] = Some(answer);
+    }
+    fn read<R>(&self, f: impl FnOnce(&ConsensusResults) -> R) -> R {
+        f(&RwLock::read(self).unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+// The canonical form nodes are compared on, see `TorusMethod::consensus_key`
+pub(crate) type ConsensusKey<'a, T> = dyn Fn(&T) -> Result<Vec<u8>> + Sync + 'a;
+
+async fn call_endpoint<T>(
+    client: &TorusClient,
+    json_rpc: &Value,
+    idx: usize,
+    endpoint: &str,
+    key: &ConsensusKey<'_, T>,
+) -> Result<NodeValue>
+where
+    T: DeserializeOwned,
+{
+    let send = client.transport.send(idx, endpoint, json_rpc);
+    let body = match timer::timeout(client.deadlines.node, send).await {
+        Some(body) => body?,
+        None => return Err(TorusError::Timeout),
+    };
+    let response: JsonRpcResponse = serde_json::from_slice(&body)?;
+    let value = response.into_result(&json_rpc["id"])?;
+    let key = key(&T::deserialize(&value)?)?;
+
+    Ok(NodeValue { key, value })
+}
+
+async fn handle_jsonrpc_request<S, T>(
+    client: &TorusClient,
+    json_rpc: &Value,
+    endpoint: &str,
+    map: S,
+    idx: usize,
+    consensus_num: usize,
+    key: &ConsensusKey<'_, T>,
+) -> Result<Option<T>>
+where
+    S: SharedResults,
+    T: DeserializeOwned,
+{
+    // call endpoint and update the shared map with the result
+    let start = Instant::now();
+    let (result, attempts) = with_retries(&client.retry, client.deadlines.lookup, || {
+        call_endpoint(client, json_rpc, idx, endpoint, key)
+    })
+    .await;
+    map.set(
+        idx,
+        NodeAnswer {
+            result,
+            latency: start.elapsed(),
+            attempts,
+        },
+    );
+
+    // take the map and check each for consensus with at least the threshold number of nodes
+    map.read(|x| match tally(x, consensus_num) {
+        // at this point we have reach consensus so we can safely return early without needing any other endpoints to finish
+        Tally::Agreed(value) => Ok(Some(T::deserialize(value)?)),
+        // end the whole call, waiting on the other endpoints cannot change the outcome
+        Tally::Impossible(e) => Err(e),
+        Tally::Pending => Ok(None),
+    })
+}
+
+pub(crate) async fn rpc_with_consensus<S, T>(
+    client: &TorusClient,
+    json_value: &Value,
+    policy: ConsensusPolicy,
+    key: &ConsensusKey<'_, T>,
+) -> Result<Consensus<T>>
+where
+    S: SharedResults,
+    T: DeserializeOwned,
+{
+    rpc_with_consensus_report::<S, T>(client, json_value, policy, key)
+        .await
+        .0
+}
+
+// same as rpc_with_consensus but also reports what every node answered, whether or not consensus was reached
+pub(crate) async fn rpc_with_consensus_report<S, T>(
+    client: &TorusClient,
+    json_value: &Value,
+    policy: ConsensusPolicy,
+    key: &ConsensusKey<'_, T>,
+) -> (Result<Consensus<T>>, ConsensusReport)
+where
+    S: SharedResults,
+    T: DeserializeOwned,
+{
+    let endpoints = client.endpoints();
+    let consensus_num = match policy.required(client.threshold(), endpoints.len()) {
+        Ok(n) => n,
+        Err(e) => return (Err(e), ConsensusReport::default()),
+    };
+    let init: ConsensusResults = endpoints.iter().map(|_| None).collect();
+
+    let map = S::new(init);
+    let mut vec_futures: FuturesUnordered<_> = endpoints
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            Box::pin(handle_jsonrpc_request(
+                client,
+                json_value,
+                s,
+                map.clone(),
+                i,
+                consensus_num,
+                key,
+            ))
+        })
+        .collect();
+
+    let deadline = client.deadlines.lookup;
+    let outcome = timer::timeout(deadline, first_decision(&mut vec_futures)).await;
+    // nodes that have not answered yet are cancelled here
+    drop(vec_futures);
+
+    map.read(|results| {
+        let outcome = match outcome {
+            Some(value) => value.map(|value| Consensus {
+                value,
+                policy,
+                required: consensus_num,
+            }),
+            None => Err(deadline_exceeded(results, endpoints, deadline)),
+        };
+        (outcome, report(results, endpoints, consensus_num))
+    })
+}
+
+// query every endpoint to completion instead of returning at the first consensus, up to the
+// lookup deadline
+pub(crate) async fn rpc_audit<T>(
+    client: &TorusClient,
+    json_value: &Value,
+    policy: ConsensusPolicy,
+    key: &ConsensusKey<'_, T>,
+) -> Result<Audit<Option<T>>>
+where
+    T: DeserializeOwned,
+{
+    let endpoints = client.endpoints();
+    let consensus_num = policy.required(client.threshold(), endpoints.len())?;
+
+    let mut answers: FuturesUnordered<_> = endpoints
+        .iter()
+        .enumerate()
+        .map(|(i, s)| async move {
+            let start = Instant::now();
+            let (result, attempts) = with_retries(&client.retry, client.deadlines.lookup, || {
+                call_endpoint(client, json_value, i, s, key)
+            })
+            .await;
+            let answer = NodeAnswer {
+                result,
+                latency: start.elapsed(),
+                attempts,
+            };
+            (i, answer)
+        })
+        .collect();
+
+    let mut results: ConsensusResults = endpoints.iter().map(|_| None).collect();
+    timer::timeout(client.deadlines.lookup, async {
+        while let Some((i, answer)) = answers.next().await {
+            results[i] = Some(answer);
+        }
+    })
+    .await;
+    // nodes that have not answered yet are cancelled here and reported as such
+    drop(answers);
+
+    audit(&results, endpoints, consensus_num, policy)
+}
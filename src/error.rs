@@ -0,0 +1,152 @@
+use crate::IdFormat;
+use std::time::Duration;
+use thiserror::Error;
+
+/// The ways a Torus lookup can fail
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TorusError {
+    #[error("no torus endpoints configured")]
+    NoEndpoints,
+    #[error("consensus threshold of {required} cannot be met by {nodes} endpoints")]
+    InvalidThreshold { required: usize, nodes: usize },
+    /// the nodes answered but not enough of them agree, and the pending ones cannot change that
+    #[error(
+        "no consensus, {required} of {nodes} nodes must agree: {}",
+        describe_groups(groups, errors)
+    )]
+    NoConsensus {
+        required: usize,
+        nodes: usize,
+        groups: Vec<ConsensusGroup>,
+        errors: Vec<NodeFailure>,
+    },
+    /// every node failed, with the error from each
+    #[error("all nodes failed: {}", describe_failures(.0))]
+    AllNodesFailed(Vec<NodeFailure>),
+    #[error("malformed response: {0}")]
+    MalformedResponse(String),
+    /// enough nodes agree that nothing is assigned to the id or key
+    #[error("key not found")]
+    KeyNotFound,
+    /// a node answered with a JSON-RPC error object
+    #[error("json-rpc error {code}: {message}{}", .data.as_ref().map(|d| format!(" ({})", d)).unwrap_or_default())]
+    Rpc {
+        code: i64,
+        message: String,
+        data: Option<String>,
+    },
+    #[error("invalid public key: {0}")]
+    InvalidPublicKey(PublicKeyError),
+    #[error("invalid verifier name {0:?}")]
+    InvalidVerifier(String),
+    #[error("invalid verifier id: {0}")]
+    InvalidVerifierId(String),
+    /// the same id resolved to different keys depending on how it was written
+    #[error("the id formats {0:?} resolve to different keys")]
+    FormatConflict(Vec<IdFormat>),
+    /// a node did not answer within its deadline
+    #[error("timed out")]
+    Timeout,
+    /// the whole lookup did not finish within its deadline
+    #[error("lookup deadline of {deadline:?} exceeded, still waiting on {}", .pending.join(", "))]
+    DeadlineExceeded {
+        deadline: Duration,
+        pending: Vec<String>,
+    },
+    #[error("http status {status}")]
+    HttpStatus {
+        status: u16,
+        /// the Retry-After a 429 or 503 response asked for
+        retry_after: Option<Duration>,
+    },
+    #[error("transport error: {0}")]
+    Transport(String),
+}
+
+/// Why a public key a node returned was rejected
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PublicKeyError {
+    #[error("coordinate is not hex: {0}")]
+    MalformedHex(String),
+    #[error("coordinate of {len} hex digits does not fit in 32 bytes")]
+    Oversized { len: usize },
+    #[error("point is not on secp256k1")]
+    NotOnCurve,
+    #[error("point at infinity")]
+    Infinity,
+}
+
+/// Nodes that returned the same result, identified by the sha256 of its canonical form
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsensusGroup {
+    pub nodes: Vec<usize>,
+    pub hash: [u8; 32],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeFailure {
+    pub node: usize,
+    pub error: TorusError,
+}
+
+fn describe_failures(errors: &[NodeFailure]) -> String {
+    errors
+        .iter()
+        .map(|f| format!("node {}: {}", f.node, f.error))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn describe_groups(groups: &[ConsensusGroup], errors: &[NodeFailure]) -> String {
+    let mut described: Vec<String> = groups
+        .iter()
+        .map(|g| format!("nodes {:?} returned {}", g.nodes, hex::encode(&g.hash[..4])))
+        .collect();
+    if !errors.is_empty() {
+        described.push(describe_failures(errors));
+    }
+    described.join("; ")
+}
+
+#[cfg(feature = "tokio")]
+impl From<reqwest::Error> for TorusError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            TorusError::Timeout
+        } else if e.is_decode() {
+            TorusError::MalformedResponse(e.to_string())
+        } else if let Some(status) = e.status() {
+            TorusError::HttpStatus {
+                status: status.as_u16(),
+                retry_after: None,
+            }
+        } else {
+            TorusError::Transport(e.to_string())
+        }
+    }
+}
+
+#[cfg(feature = "isahc")]
+impl From<isahc::Error> for TorusError {
+    fn from(e: isahc::Error) -> Self {
+        if e.is_timeout() {
+            TorusError::Timeout
+        } else {
+            TorusError::Transport(e.to_string())
+        }
+    }
+}
+
+impl From<serde_json::Error> for TorusError {
+    fn from(e: serde_json::Error) -> Self {
+        TorusError::MalformedResponse(e.to_string())
+    }
+}
+
+impl From<bincode::Error> for TorusError {
+    fn from(e: bincode::Error) -> Self {
+        TorusError::MalformedResponse(e.to_string())
+    }
+}
+
+pub type Result<T, E = TorusError> = std::result::Result<T, E>;
@@ -1,55 +1,137 @@
-use anyhow::{bail, ensure, Result};
-use reqwest::{
-    header::{self, HeaderMap},
-    Client,
+use consensus::{
+    audit, deadline_exceeded, first_decision, report, tally, NodeAnswer, NodeValue, Tally,
 };
-use serde::{Deserialize, Serialize};
+use futures::stream::FuturesUnordered;
+#[cfg(feature = "tokio")]
+use reqwest::Client;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::{json, Value};
 use sha2::{Digest, Sha256};
-use std::{cell::RefCell, rc::Rc, sync::Arc, time::Duration};
-use tokio::sync::RwLock;
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, OnceLock, PoisonError, RwLock,
+    },
+    time::{Duration, Instant},
+};
 
-mod consensus_multi_thread;
-mod consensus_single_thread;
+pub mod blocking;
+mod consensus;
+mod consensus_engine;
+mod error;
+mod method;
+#[cfg(feature = "mock-server")]
+pub mod mock;
+mod network;
+mod retry;
 #[cfg(test)]
 mod tests;
+mod timer;
+mod transport;
+mod verifier;
 
 // NodeJs
 // import FetchNodeDetails from "@toruslabs/fetch-node-details";
 // const fetchNodeDetails = new FetchNodeDetails({ network: "mainnet" });
 // fetchNodeDetails.getNodeDetails({ verifier: "twitter", verifierId: "partisia-twitter-mainnet" }).then((nodeInfo) => console.log(nodeInfo));
 
-const TORUS_ENDPOINTS: [&'static str; 5] = [
-    "https://sapphire-1.auth.network/sss/mainnet/jrpc",
-    "https://sapphire-2.auth.network/sss/mainnet/jrpc",
-    "https://sapphire-3.auth.network/sss/mainnet/jrpc",
-    "https://sapphire-4.auth.network/sss/mainnet/jrpc",
-    "https://sapphire-5.auth.network/sss/mainnet/jrpc",
-];
-
-const VERIFIER_TWITTER: &'static str = "partisia-twitter-mainnet";
-const VERIFIER_DISCORD: &'static str = "partisia-discord";
-const VERIFIER_APPLE: &'static str = "parti-apple";
+pub use consensus::{Audit, Consensus, ConsensusPolicy, ConsensusReport, NodeOutcome, NodeReport};
+pub use error::{ConsensusGroup, NodeFailure, PublicKeyError, Result, TorusError};
+pub use method::{
+    KeyLookupParams, KeyLookupRequest, TorusMethod, VerifierLookupParams, VerifierLookupRequest,
+};
+#[cfg(test)]
+use network::TORUS_ENDPOINTS;
+pub use network::{TorusNetwork, VerifierNames};
+#[cfg(any(feature = "tokio", feature = "isahc"))]
+use retry::retry_after;
+use retry::with_retries;
+pub use retry::RetryPolicy;
+#[cfg(feature = "isahc")]
+pub use transport::IsahcTransport;
+#[cfg(feature = "tokio")]
+pub use transport::ReqwestTransport;
+pub use transport::TorusTransport;
+pub use verifier::{IdFormat, Verifier, VerifierId};
 
-// the consensus results are None if still pending a result from the rpc call
-type ConsensusResults = [Option<Result<Vec<u8>>>; TORUS_ENDPOINTS.len()];
-type MapRpcResultsSingleThread<T> = Rc<RefCell<T>>;
-type MapRpcResultsMultiThread<T> = Arc<RwLock<T>>;
+// the consensus results are None if still pending a result from the rpc call, one slot per endpoint
+type ConsensusResults = Vec<Option<NodeAnswer>>;
+// lookups are Send futures with the multi_thread feature, otherwise they may only be polled on one thread
+#[cfg(feature = "multi_thread")]
+type SharedConsensusResults = Arc<RwLock<ConsensusResults>>;
+#[cfg(not(feature = "multi_thread"))]
+type SharedConsensusResults = Rc<RefCell<ConsensusResults>>;
 
 fn sha256_hash(buf: &[u8]) -> [u8; 32] {
     // get the hash
     let mut hasher = Sha256::new();
     hasher.update(buf);
-    hasher
-        .finalize()
-        .try_into()
-        .expect("sha256 is always 32 bytes")
+    hasher.finalize().into()
 }
-#[derive(Debug, Deserialize, Serialize)]
-struct JsonRpc<T> {
-    // jsonrpc: String,
-    result: T,
-    // id: u8,
+// a JSON-RPC 2.0 response, carrying either a result or an error
+#[derive(Debug, Deserialize)]
+struct JsonRpcResponse {
+    jsonrpc: String,
+    id: Value,
+    #[serde(default)]
+    result: Value,
+    error: Option<JsonRpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcError {
+    code: i64,
+    message: String,
+    #[serde(default)]
+    data: Value,
+}
+
+impl JsonRpcResponse {
+    // the result of the request with id `id`
+    fn into_result(self, id: &Value) -> Result<Value> {
+        if self.jsonrpc != "2.0" {
+            return Err(TorusError::MalformedResponse(format!(
+                "unsupported jsonrpc version {:?}",
+                self.jsonrpc
+            )));
+        }
+        if self.id != *id {
+            return Err(TorusError::MalformedResponse(format!(
+                "response id {} does not match request id {}",
+                self.id, id
+            )));
+        }
+        match self.error {
+            Some(error) => Err(error.into()),
+            None => Ok(self.result),
+        }
+    }
+}
+
+impl From<JsonRpcError> for TorusError {
+    fn from(e: JsonRpcError) -> Self {
+        let data = match e.data {
+            Value::Null => None,
+            Value::String(data) => Some(data),
+            data => Some(data.to_string()),
+        };
+        // how the nodes say nothing is assigned to an id, or to a key
+        let not_found = data.as_deref().is_some_and(|data| {
+            let data = data.to_lowercase();
+            data.contains("has not yet been assigned") || data.contains("key not found")
+        });
+        if not_found {
+            return TorusError::KeyNotFound;
+        }
+        TorusError::Rpc {
+            code: e.code,
+            message: e.message,
+            data,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -66,29 +148,124 @@ pub struct TorusKey {
     address: String,
 }
 
+impl TorusKeys {
+    fn first_public_key(&self) -> Result<Option<[u8; 65]>> {
+        self.keys
+            .first()
+            .map(|f| f.derive_public_key_uncompressed())
+            .transpose()
+    }
+
+    // decoded indices and keys, and lower-cased addresses, so nodes that only write them
+    // differently still agree
+    pub(crate) fn consensus_key(&self) -> Result<Vec<u8>> {
+        let keys = self
+            .keys
+            .iter()
+            .map(|key| {
+                Ok((
+                    parse_key_index(&key.key_index)?,
+                    key.derive_public_key_uncompressed()?.to_vec(),
+                    key.address.to_lowercase(),
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(bincode::serialize(&keys)?)
+    }
+
+    pub fn to_verifier_keys(&self) -> Result<VerifierKeys> {
+        let keys = self
+            .keys
+            .iter()
+            .map(TorusKey::to_verifier_key)
+            .collect::<Result<_>>()?;
+        Ok(VerifierKeys { keys })
+    }
+}
+
 impl TorusKey {
     pub fn derive_public_key_uncompressed(&self) -> Result<[u8; 65]> {
-        let padding_len = 64 - self.pub_key_x.len();
-        let padding = "0".repeat(padding_len);
-        let pub_key_x = format!("{}{}", padding, self.pub_key_x);
+        parse_public_key(&self.pub_key_x, &self.pub_key_y)
+    }
+
+    fn to_verifier_key(&self) -> Result<VerifierKey> {
+        let index = parse_key_index(&self.key_index)?;
+        let uncompressed = self.derive_public_key_uncompressed()?;
+        // 02 or 03 for an even or odd y, followed by x
+        let mut compressed = [0u8; 33];
+        compressed[0] = 0x02 | (uncompressed[64] & 1);
+        compressed[1..].copy_from_slice(&uncompressed[1..33]);
+        Ok(VerifierKey {
+            index,
+            uncompressed,
+            compressed,
+            address: self.address.clone(),
+        })
+    }
+}
+
+fn parse_key_index(index: &str) -> Result<u64> {
+    u64::from_str_radix(index, 16)
+        .map_err(|_| TorusError::MalformedResponse(format!("malformed key index {}", index)))
+}
+
+// nodes drop leading zeros from the coordinates, so they are left padded back to 32 bytes
+fn parse_coordinate(hex: &str) -> Result<[u8; 32]> {
+    if hex.len() > 64 {
+        return Err(TorusError::InvalidPublicKey(PublicKeyError::Oversized {
+            len: hex.len(),
+        }));
+    }
+    let mut coordinate = [0u8; 32];
+    hex::decode_to_slice(format!("{:0>64}", hex), &mut coordinate)
+        .map_err(|e| TorusError::InvalidPublicKey(PublicKeyError::MalformedHex(e.to_string())))?;
+    Ok(coordinate)
+}
 
-        let padding_len = 64 - self.pub_key_y.len();
-        let padding = "0".repeat(padding_len);
-        let pub_key_y = format!("{}{}", padding, self.pub_key_y);
+// the uncompressed encoding of the point, only if it is on secp256k1
+fn parse_public_key(x: &str, y: &str) -> Result<[u8; 65]> {
+    let mut v = [0u8; 65];
+    v[0] = 0x04;
+    v[1..33].copy_from_slice(&parse_coordinate(x)?);
+    v[33..].copy_from_slice(&parse_coordinate(y)?);
+    // the encoding has no representation of infinity, nodes would send it as zeros
+    if v[1..].iter().all(|b| *b == 0) {
+        return Err(TorusError::InvalidPublicKey(PublicKeyError::Infinity));
+    }
+    libsecp256k1::PublicKey::parse(&v)
+        .map_err(|_| TorusError::InvalidPublicKey(PublicKeyError::NotOnCurve))?;
+    Ok(v)
+}
 
-        let pub_key_x: [u8; 32] = hex::decode(pub_key_x)?.as_slice().try_into()?;
-        let pub_key_y: [u8; 32] = hex::decode(pub_key_y)?.as_slice().try_into()?;
+/// Every key the nodes hold for a verifier id, as returned by `lookup_keys_request`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifierKeys {
+    /// in the order the nodes list them
+    pub keys: Vec<VerifierKey>,
+}
 
-        let mut v = Vec::with_capacity(65);
-        v.push(0x04);
-        v.extend_from_slice(pub_key_x.as_slice());
-        v.extend_from_slice(pub_key_y.as_slice());
-        Ok(v.as_slice().try_into()?)
+impl VerifierKeys {
+    /// The key `lookup_request` returns, the first one the nodes list
+    pub fn primary(&self) -> Option<&VerifierKey> {
+        self.keys.first()
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifierKey {
+    /// the key index the nodes assigned, parsed from hex
+    pub index: u64,
+    /// 04 followed by x and y
+    pub uncompressed: [u8; 65],
+    /// 02 or 03 followed by x
+    pub compressed: [u8; 33],
+    /// the ethereum address of the key, as the nodes return it
+    pub address: String,
+}
+
+/// What the nodes answer a `KeyLookupRequest` with
 #[derive(Debug, Deserialize, Serialize)]
-struct TorusLookup {
+pub struct TorusLookup {
     #[serde(rename = "Index")]
     index: String,
     #[serde(rename = "PublicKey")]
@@ -99,11 +276,10 @@ struct TorusLookup {
     verifiers: TorusVerifier,
 }
 
+// verifier name -> ids, kept ordered so every node's answer serializes the same way
 #[derive(Debug, Deserialize, Serialize)]
-struct TorusVerifier {
-    #[serde(rename = "partisia-twitter-mainnet")]
-    partisia: Option<Vec<String>>,
-}
+#[serde(transparent)]
+struct TorusVerifier(BTreeMap<String, Vec<String>>);
 
 #[derive(Debug, Deserialize, Serialize)]
 struct TorusPublicKey {
@@ -113,129 +289,568 @@ struct TorusPublicKey {
     y: String,
 }
 
-#[derive(Debug, Clone, Copy)]
-pub enum Verifier {
-    Twitter,
-    Discord,
-    Apple,
+/// The key an id resolved to when trying each of its formats, see `lookup_request_any_format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatLookup {
+    pub key: Option<[u8; 65]>,
+    /// the first format the key was found under
+    pub format: Option<IdFormat>,
 }
 
-#[cfg(feature = "multi_thread")]
-pub mod multi_thread {
-    use super::*;
+/// Everyone a public key is registered to, as returned by `key_lookup_request`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyLookup {
+    /// the key index the nodes assigned, parsed from hex
+    pub index: u64,
+    /// the threshold of the key's secret sharing
+    pub threshold: u16,
+    /// every verifier id linked to the key, ordered by verifier name and then as the nodes list them
+    pub owners: Vec<VerifierId>,
+}
 
-    pub async fn lookup_request(
-        verifier_id: &'_ str,
-        verifier_type: Verifier,
-    ) -> Result<Option<[u8; 65]>> {
-        let verifier = match verifier_type {
-            Verifier::Twitter => VERIFIER_TWITTER,
-            Verifier::Discord => VERIFIER_DISCORD,
-            Verifier::Apple => VERIFIER_APPLE,
-        };
-        let json_rpc = json!({
-          "jsonrpc": "2.0",
-          "id": 10,
-          "method": "VerifierLookupRequest",
-          "params": {
-            "verifier": verifier,
-            "verifier_id": verifier_id
-          }
-        });
+impl KeyLookup {
+    /// The ids registered with `verifier`, oldest first
+    pub fn ids<'a>(&'a self, verifier: &'a Verifier) -> impl Iterator<Item = &'a VerifierId> + 'a {
+        self.owners.iter().filter(move |o| o.verifier() == verifier)
+    }
+}
 
-        let torus_keys: TorusKeys = consensus_multi_thread::rpc_with_consensus(&json_rpc).await?;
-        let public_key = torus_keys
-            .keys
-            .first()
-            .map(|f| f.derive_public_key_uncompressed())
-            .transpose()?;
-        Ok(public_key)
+/// A client for a set of Torus nodes. Lookups are sent to every endpoint of the
+/// network and succeed once the network's threshold of nodes agree.
+#[derive(Clone)]
+pub struct TorusClient {
+    network: TorusNetwork,
+    endpoints: Vec<String>,
+    threshold: usize,
+    verifiers: VerifierNames,
+    deadlines: Deadlines,
+    retry: RetryPolicy,
+    transport: Arc<dyn TorusTransport>,
+    // false once a transport is given, which then keeps its own connect timeout
+    default_transport: bool,
+}
+
+impl std::fmt::Debug for TorusClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TorusClient")
+            .field("network", &self.network)
+            .field("deadlines", &self.deadlines)
+            .field("retry", &self.retry)
+            .finish_non_exhaustive()
     }
-    pub async fn key_lookup_request(
-        pub_key_x: &[u8; 32],
-        pub_key_y: &[u8; 32],
-    ) -> Result<Option<u64>> {
-        let json_rpc = json!({
+}
+
+/// How long a lookup may wait on the nodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deadlines {
+    /// establishing a connection to a node
+    pub connect: Duration,
+    /// a single node call, from sending the request to reading the response
+    pub node: Duration,
+    /// the whole lookup, after which nodes that have not answered are abandoned
+    pub lookup: Duration,
+}
+
+impl Default for Deadlines {
+    fn default() -> Self {
+        Self {
+            connect: Duration::from_millis(3000),
+            node: Duration::from_millis(3000),
+            lookup: Duration::from_millis(3000),
+        }
+    }
+}
+
+impl TorusClient {
+    /// A client for a custom node set, see `TorusNetwork::custom`
+    pub fn new<I, S>(endpoints: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self::with_network(TorusNetwork::custom(endpoints))
+    }
+
+    pub fn with_network(network: TorusNetwork) -> Self {
+        Self {
+            endpoints: network.endpoints(),
+            threshold: network.threshold(),
+            verifiers: network.verifiers(),
+            network,
+            deadlines: Deadlines::default(),
+            retry: RetryPolicy::default(),
+            transport: transport::default_transport(Deadlines::default().connect),
+            default_transport: true,
+        }
+    }
+
+    /// The connect deadline only applies to the default transport, one given with
+    /// `with_http_client` or `with_transport` keeps its own connect timeout
+    pub fn with_deadlines(mut self, deadlines: Deadlines) -> Self {
+        if self.default_transport {
+            self.transport = transport::default_transport(deadlines.connect);
+        }
+        self.deadlines = deadlines;
+        self
+    }
+
+    pub fn deadlines(&self) -> &Deadlines {
+        &self.deadlines
+    }
+
+    /// A node is not retried when the retry would start after the lookup deadline
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    /// Use an existing reqwest client, e.g. to share its connection pool with the rest of an application.
+    /// The connect deadline is then whatever that client was built with.
+    #[cfg(feature = "tokio")]
+    pub fn with_http_client(self, http: Client) -> Self {
+        self.with_transport(ReqwestTransport::from_client(http))
+    }
+
+    /// Send node calls through something other than reqwest, e.g. in-memory nodes in tests
+    pub fn with_transport(mut self, transport: impl TorusTransport + 'static) -> Self {
+        self.transport = Arc::new(transport);
+        self.default_transport = false;
+        self
+    }
+
+    pub fn network(&self) -> &TorusNetwork {
+        &self.network
+    }
+
+    pub fn endpoints(&self) -> &[String] {
+        &self.endpoints
+    }
+
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Runs a method on the nodes, accepting the response once enough of them agree on it
+    pub async fn call<M: TorusMethod>(&self, params: M::Params) -> Result<M::Response> {
+        let consensus = self
+            .call_with_policy::<M>(params, ConsensusPolicy::default())
+            .await?;
+        Ok(consensus.value)
+    }
+    pub async fn call_with_policy<M: TorusMethod>(
+        &self,
+        params: M::Params,
+        policy: ConsensusPolicy,
+    ) -> Result<Consensus<M::Response>> {
+        let json_rpc = Self::method_rpc::<M>(&params)?;
+        consensus_engine::rpc_with_consensus::<SharedConsensusResults, _>(
+            self,
+            &json_rpc,
+            policy,
+            &M::consensus_key,
+        )
+        .await
+    }
+
+    /// Calls a method that has no `TorusMethod`. Results are compared on what `key` extracts from
+    /// them, see `TorusMethod::consensus_key` for what it should return.
+    pub async fn request_with_consensus<T, K>(
+        &self,
+        method: &str,
+        params: Value,
+        policy: ConsensusPolicy,
+        key: impl Fn(&T) -> Result<K> + Sync,
+    ) -> Result<Consensus<T>>
+    where
+        T: DeserializeOwned,
+        K: Serialize,
+    {
+        let json_rpc = Self::rpc(method, params);
+        consensus_engine::rpc_with_consensus::<SharedConsensusResults, _>(
+            self,
+            &json_rpc,
+            policy,
+            &|result: &T| Ok(bincode::serialize(&key(result)?)?),
+        )
+        .await
+    }
+
+    fn rpc(method: &str, params: Value) -> Value {
+        // unique within the process, so a response can be matched to its request
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+        json!({
           "jsonrpc": "2.0",
-          "id": 10,
-          "method": "KeyLookupRequest",
-          "params": {
-            "pub_key_X": hex::encode(pub_key_x),
-            "pub_key_Y": hex::encode(pub_key_y)
-          }
-        });
+          "id": NEXT_ID.fetch_add(1, Ordering::Relaxed),
+          "method": method,
+          "params": params
+        })
+    }
 
-        let torus_lookup: TorusLookup =
-            consensus_multi_thread::rpc_with_consensus(&json_rpc).await?;
-        if let Some(ary_ids) = torus_lookup.verifiers.partisia {
-            ensure!(ary_ids.len() > 0, "No id found for partisia");
-
-            // take the last key which will be formatted like "twitter|1415723267256639488" and split it
-            let twitter_id = &ary_ids[ary_ids.len() - 1]
-                .splitn(2, "|")
-                .collect::<Vec<&str>>();
-            ensure!(twitter_id.len() == 2, "malformed twitter key");
-            Ok(Some(twitter_id[1].parse()?))
-        } else {
-            // No key found for partisia
-            Ok(None)
+    fn method_rpc<M: TorusMethod>(params: &M::Params) -> Result<Value> {
+        Ok(Self::rpc(M::NAME, serde_json::to_value(params)?))
+    }
+
+    fn verifier_lookup_params(
+        &self,
+        verifier_id: &VerifierId,
+        format: IdFormat,
+    ) -> VerifierLookupParams {
+        VerifierLookupParams {
+            verifier: self.verifiers.name(verifier_id.verifier()).to_string(),
+            verifier_id: verifier_id.format(format),
         }
     }
 }
 
-#[cfg(feature = "single_threaded")]
-pub mod single_threaded {
-    use super::*;
+impl Default for TorusClient {
+    fn default() -> Self {
+        Self::with_network(TorusNetwork::default())
+    }
+}
 
-    pub async fn lookup_request(
-        verifier_id: &'_ str,
-        verifier_type: Verifier,
-    ) -> Result<TorusKeys> {
-        let verifier = match verifier_type {
-            Verifier::Twitter => VERIFIER_TWITTER,
-            Verifier::Discord => VERIFIER_DISCORD,
-            Verifier::Apple => VERIFIER_APPLE,
+// the mainnet client behind the free lookup functions, created once so its connections are reused
+fn default_client() -> &'static TorusClient {
+    static DEFAULT_CLIENT: OnceLock<TorusClient> = OnceLock::new();
+    DEFAULT_CLIENT.get_or_init(TorusClient::default)
+}
+
+impl TorusLookup {
+    pub(crate) fn consensus_key(&self) -> Result<Vec<u8>> {
+        Ok(bincode::serialize(&(
+            parse_key_index(&self.index)?,
+            parse_public_key(&self.public_key.x, &self.public_key.y)?.to_vec(),
+            self.threshold,
+            &self.verifiers.0,
+        ))?)
+    }
+
+    /// Reads the verifier ids with the verifier names of the network the lookup was made on
+    pub fn into_key_lookup(self, names: &VerifierNames) -> Result<KeyLookup> {
+        let index = parse_key_index(&self.index)?;
+        let mut owners = Vec::new();
+        for (name, ids) in self.verifiers.0 {
+            let verifier = names.verifier(&name);
+            for id in ids {
+                owners.push(VerifierId::unchecked(verifier.clone(), &id));
+            }
+        }
+        Ok(KeyLookup {
+            index,
+            threshold: self.threshold,
+            owners,
+        })
+    }
+}
+
+impl TorusClient {
+    pub async fn lookup_request(&self, verifier_id: &VerifierId) -> Result<Option<[u8; 65]>> {
+        let consensus = self
+            .lookup_request_with_policy(verifier_id, ConsensusPolicy::default())
+            .await?;
+        Ok(consensus.value)
+    }
+    pub async fn lookup_request_with_policy(
+        &self,
+        verifier_id: &VerifierId,
+        policy: ConsensusPolicy,
+    ) -> Result<Consensus<Option<[u8; 65]>>> {
+        self.lookup_request_with_report(verifier_id, policy).await.0
+    }
+    /// Like `lookup_request_with_policy`, also reporting what each node answered
+    pub async fn lookup_request_with_report(
+        &self,
+        verifier_id: &VerifierId,
+        policy: ConsensusPolicy,
+    ) -> (Result<Consensus<Option<[u8; 65]>>>, ConsensusReport) {
+        let params = self.verifier_lookup_params(verifier_id, verifier_id.formats()[0]);
+        let json_rpc = match Self::method_rpc::<VerifierLookupRequest>(&params) {
+            Ok(json_rpc) => json_rpc,
+            Err(e) => return (Err(e), ConsensusReport::default()),
         };
-        let json_rpc = json!({
-          "jsonrpc": "2.0",
-          "id": 10,
-          "method": "VerifierLookupRequest",
-          "params": {
-            "verifier": verifier,
-            "verifier_id": verifier_id
-          }
+
+        let (torus_keys, report) =
+            consensus_engine::rpc_with_consensus_report::<SharedConsensusResults, TorusKeys>(
+                self,
+                &json_rpc,
+                policy,
+                &VerifierLookupRequest::consensus_key,
+            )
+            .await;
+        let public_key = self.found(torus_keys, policy).and_then(|torus_keys| {
+            torus_keys.try_map(|k| Ok(k.map(|k| k.first_public_key()).transpose()?.flatten()))
         });
+        (public_key, report)
+    }
+    /// Every key the nodes hold for the id, where `lookup_request` only returns the primary one
+    pub async fn lookup_keys_request(&self, verifier_id: &VerifierId) -> Result<VerifierKeys> {
+        let consensus = self
+            .lookup_keys_request_with_policy(verifier_id, ConsensusPolicy::default())
+            .await?;
+        Ok(consensus.value)
+    }
+    pub async fn lookup_keys_request_with_policy(
+        &self,
+        verifier_id: &VerifierId,
+        policy: ConsensusPolicy,
+    ) -> Result<Consensus<VerifierKeys>> {
+        let params = self.verifier_lookup_params(verifier_id, verifier_id.formats()[0]);
+        let torus_keys = self
+            .call_with_policy::<VerifierLookupRequest>(params, policy)
+            .await;
+        self.found(torus_keys, policy)?.try_map(|k| match k {
+            Some(k) => k.to_verifier_keys(),
+            None => Ok(VerifierKeys { keys: Vec::new() }),
+        })
+    }
+    /// Waits for every node, up to the lookup deadline, and reports which of them agree with each
+    /// other. The value is `Some(None)` when enough nodes agree that nothing is assigned to the id.
+    pub async fn audit_lookup_request(
+        &self,
+        verifier_id: &VerifierId,
+        policy: ConsensusPolicy,
+    ) -> Result<Audit<Option<[u8; 65]>>> {
+        let params = self.verifier_lookup_params(verifier_id, verifier_id.formats()[0]);
+        let json_rpc = Self::method_rpc::<VerifierLookupRequest>(&params)?;
+
+        let torus_keys: Audit<Option<TorusKeys>> = consensus_engine::rpc_audit(
+            self,
+            &json_rpc,
+            policy,
+            &VerifierLookupRequest::consensus_key,
+        )
+        .await?;
+        torus_keys.try_map(|k| match k {
+            Some(k) => k.first_public_key(),
+            None => Ok(None),
+        })
+    }
+    /// Looks the id up in every format the nodes may store it in, see `VerifierId::formats`.
+    /// Formats the id is not assigned under are skipped. Any other failure is an error, as are
+    /// formats resolving to different keys.
+    pub async fn lookup_request_any_format(
+        &self,
+        verifier_id: &VerifierId,
+        policy: ConsensusPolicy,
+    ) -> Result<FormatLookup> {
+        let formats = verifier_id.formats();
+        let lookups = futures::future::join_all(formats.iter().map(|&format| async move {
+            let params = self.verifier_lookup_params(verifier_id, format);
+            let torus_keys = self
+                .call_with_policy::<VerifierLookupRequest>(params, policy)
+                .await;
+            match self.found(torus_keys, policy)?.value {
+                Some(k) => k.first_public_key(),
+                None => Ok(None),
+            }
+        }))
+        .await;
 
-        Ok(consensus_single_thread::rpc_with_consensus(&json_rpc).await?)
+        // a failed format could have resolved to a different key, so only unassigned ones are skipped
+        let mut resolved: Vec<(IdFormat, [u8; 65])> = Vec::new();
+        for (&format, lookup) in formats.iter().zip(lookups) {
+            if let Some(key) = lookup? {
+                resolved.push((format, key));
+            }
+        }
+        match resolved.first() {
+            Some(&(_, key)) if resolved.iter().any(|&(_, k)| k != key) => Err(
+                TorusError::FormatConflict(resolved.iter().map(|&(f, _)| f).collect()),
+            ),
+            Some(&(format, key)) => Ok(FormatLookup {
+                key: Some(key),
+                format: Some(format),
+            }),
+            None => Ok(FormatLookup {
+                key: None,
+                format: None,
+            }),
+        }
     }
+    /// Every verifier and id the key is registered to
     pub async fn key_lookup_request(
+        &self,
         pub_key_x: &[u8; 32],
         pub_key_y: &[u8; 32],
-    ) -> Result<Option<u64>> {
-        let json_rpc = json!({
-          "jsonrpc": "2.0",
-          "id": 10,
-          "method": "KeyLookupRequest",
-          "params": {
-            "pub_key_X": hex::encode(pub_key_x),
-            "pub_key_Y": hex::encode(pub_key_y)
-          }
-        });
+    ) -> Result<KeyLookup> {
+        let consensus = self
+            .key_lookup_request_with_policy(pub_key_x, pub_key_y, ConsensusPolicy::default())
+            .await?;
+        Ok(consensus.value)
+    }
+    pub async fn key_lookup_request_with_policy(
+        &self,
+        pub_key_x: &[u8; 32],
+        pub_key_y: &[u8; 32],
+        policy: ConsensusPolicy,
+    ) -> Result<Consensus<KeyLookup>> {
+        let torus_lookup = self
+            .call_with_policy::<KeyLookupRequest>(
+                KeyLookupParams::new(pub_key_x, pub_key_y),
+                policy,
+            )
+            .await?;
+        torus_lookup.try_map(|l| l.into_key_lookup(&self.verifiers))
+    }
+    /// The id the key is registered under with `verifier`, e.g. an email for a google verifier
+    pub async fn key_lookup_request_for(
+        &self,
+        pub_key_x: &[u8; 32],
+        pub_key_y: &[u8; 32],
+        verifier_type: Verifier,
+    ) -> Result<Option<VerifierId>> {
+        let consensus = self
+            .key_lookup_request_for_with_policy(
+                pub_key_x,
+                pub_key_y,
+                verifier_type,
+                ConsensusPolicy::default(),
+            )
+            .await?;
+        Ok(consensus.value)
+    }
+    pub async fn key_lookup_request_for_with_policy(
+        &self,
+        pub_key_x: &[u8; 32],
+        pub_key_y: &[u8; 32],
+        verifier_type: Verifier,
+        policy: ConsensusPolicy,
+    ) -> Result<Consensus<Option<VerifierId>>> {
+        let torus_lookup = self
+            .call_with_policy::<KeyLookupRequest>(
+                KeyLookupParams::new(pub_key_x, pub_key_y),
+                policy,
+            )
+            .await;
+        // the nodes list the most recently linked id last
+        self.found(torus_lookup, policy)?.try_map(|l| match l {
+            Some(l) => {
+                let lookup = l.into_key_lookup(&self.verifiers)?;
+                Ok(lookup.ids(&verifier_type).last().cloned())
+            }
+            None => Ok(None),
+        })
+    }
 
-        let torus_lookup: TorusLookup =
-            consensus_single_thread::rpc_with_consensus(&json_rpc).await?;
-        if let Some(ary_ids) = torus_lookup.verifiers.partisia {
-            ensure!(ary_ids.len() > 0, "No id found for partisia");
-
-            // take the last key which will be formatted like "twitter|1415723267256639488" and split it
-            let twitter_id = &ary_ids[ary_ids.len() - 1]
-                .splitn(2, "|")
-                .collect::<Vec<&str>>();
-            ensure!(twitter_id.len() == 2, "malformed twitter key");
-            Ok(Some(twitter_id[1].parse()?))
-        } else {
-            // No key found for partisia
-            Ok(None)
+    // nodes agreeing that nothing is assigned is an answer to a lookup rather than a failure
+    fn found<T>(
+        &self,
+        consensus: Result<Consensus<T>>,
+        policy: ConsensusPolicy,
+    ) -> Result<Consensus<Option<T>>> {
+        match consensus {
+            Ok(consensus) => Ok(consensus.map(Some)),
+            Err(TorusError::KeyNotFound) => Ok(Consensus {
+                value: None,
+                policy,
+                required: policy.required(self.threshold(), self.endpoints().len())?,
+            }),
+            Err(e) => Err(e),
         }
     }
 }
+
+pub async fn lookup_request(verifier_id: &VerifierId) -> Result<Option<[u8; 65]>> {
+    default_client().lookup_request(verifier_id).await
+}
+pub async fn lookup_request_with_policy(
+    verifier_id: &VerifierId,
+    policy: ConsensusPolicy,
+) -> Result<Consensus<Option<[u8; 65]>>> {
+    default_client()
+        .lookup_request_with_policy(verifier_id, policy)
+        .await
+}
+pub async fn lookup_request_with_report(
+    verifier_id: &VerifierId,
+    policy: ConsensusPolicy,
+) -> (Result<Consensus<Option<[u8; 65]>>>, ConsensusReport) {
+    default_client()
+        .lookup_request_with_report(verifier_id, policy)
+        .await
+}
+pub async fn lookup_keys_request(verifier_id: &VerifierId) -> Result<VerifierKeys> {
+    default_client().lookup_keys_request(verifier_id).await
+}
+pub async fn lookup_keys_request_with_policy(
+    verifier_id: &VerifierId,
+    policy: ConsensusPolicy,
+) -> Result<Consensus<VerifierKeys>> {
+    default_client()
+        .lookup_keys_request_with_policy(verifier_id, policy)
+        .await
+}
+pub async fn audit_lookup_request(
+    verifier_id: &VerifierId,
+    policy: ConsensusPolicy,
+) -> Result<Audit<Option<[u8; 65]>>> {
+    default_client()
+        .audit_lookup_request(verifier_id, policy)
+        .await
+}
+pub async fn lookup_request_any_format(
+    verifier_id: &VerifierId,
+    policy: ConsensusPolicy,
+) -> Result<FormatLookup> {
+    default_client()
+        .lookup_request_any_format(verifier_id, policy)
+        .await
+}
+pub async fn key_lookup_request(pub_key_x: &[u8; 32], pub_key_y: &[u8; 32]) -> Result<KeyLookup> {
+    default_client()
+        .key_lookup_request(pub_key_x, pub_key_y)
+        .await
+}
+pub async fn key_lookup_request_with_policy(
+    pub_key_x: &[u8; 32],
+    pub_key_y: &[u8; 32],
+    policy: ConsensusPolicy,
+) -> Result<Consensus<KeyLookup>> {
+    default_client()
+        .key_lookup_request_with_policy(pub_key_x, pub_key_y, policy)
+        .await
+}
+
+pub async fn key_lookup_request_for(
+    pub_key_x: &[u8; 32],
+    pub_key_y: &[u8; 32],
+    verifier_type: Verifier,
+) -> Result<Option<VerifierId>> {
+    default_client()
+        .key_lookup_request_for(pub_key_x, pub_key_y, verifier_type)
+        .await
+}
+pub async fn key_lookup_request_for_with_policy(
+    pub_key_x: &[u8; 32],
+    pub_key_y: &[u8; 32],
+    verifier_type: Verifier,
+    policy: ConsensusPolicy,
+) -> Result<Consensus<Option<VerifierId>>> {
+    default_client()
+        .key_lookup_request_for_with_policy(pub_key_x, pub_key_y, verifier_type, policy)
+        .await
+}
+
+// the same lookups under the module of the feature that selects them
+#[cfg(feature = "multi_thread")]
+pub mod multi_thread {
+    pub use super::{
+        audit_lookup_request, key_lookup_request, key_lookup_request_for,
+        key_lookup_request_for_with_policy, key_lookup_request_with_policy, lookup_keys_request,
+        lookup_keys_request_with_policy, lookup_request, lookup_request_any_format,
+        lookup_request_with_policy, lookup_request_with_report,
+    };
+}
+
+#[cfg(feature = "single_threaded")]
+pub mod single_threaded {
+    pub use super::{
+        audit_lookup_request, key_lookup_request, key_lookup_request_for,
+        key_lookup_request_for_with_policy, key_lookup_request_with_policy, lookup_keys_request,
+        lookup_keys_request_with_policy, lookup_request, lookup_request_any_format,
+        lookup_request_with_policy, lookup_request_with_report,
+    };
+}
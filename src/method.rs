@@ -0,0 +1,67 @@
+use crate::{Result, TorusKeys, TorusLookup};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// A JSON-RPC method the nodes serve, run with consensus by `TorusClient::call`
+pub trait TorusMethod {
+    /// the method name sent to the nodes
+    const NAME: &'static str;
+    type Params: Serialize;
+    type Response: DeserializeOwned;
+
+    /// The canonical form responses are compared on. It should decode and normalize whatever two
+    /// honest nodes may write differently and leave out fields that do not matter. An error counts
+    /// the node as failed.
+    fn consensus_key(response: &Self::Response) -> Result<Vec<u8>>;
+}
+
+/// The keys assigned to a verifier id
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifierLookupRequest;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifierLookupParams {
+    /// the verifier name registered on the network, see `VerifierNames`
+    pub verifier: String,
+    pub verifier_id: String,
+}
+
+impl TorusMethod for VerifierLookupRequest {
+    const NAME: &'static str = "VerifierLookupRequest";
+    type Params = VerifierLookupParams;
+    type Response = TorusKeys;
+
+    fn consensus_key(response: &TorusKeys) -> Result<Vec<u8>> {
+        response.consensus_key()
+    }
+}
+
+/// The verifier ids a public key is registered to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyLookupRequest;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyLookupParams {
+    #[serde(rename = "pub_key_X")]
+    pub pub_key_x: String,
+    #[serde(rename = "pub_key_Y")]
+    pub pub_key_y: String,
+}
+
+impl KeyLookupParams {
+    pub fn new(pub_key_x: &[u8; 32], pub_key_y: &[u8; 32]) -> Self {
+        Self {
+            pub_key_x: hex::encode(pub_key_x),
+            pub_key_y: hex::encode(pub_key_y),
+        }
+    }
+}
+
+impl TorusMethod for KeyLookupRequest {
+    const NAME: &'static str = "KeyLookupRequest";
+    type Params = KeyLookupParams;
+    type Response = TorusLookup;
+
+    fn consensus_key(response: &TorusLookup) -> Result<Vec<u8>> {
+        response.consensus_key()
+    }
+}
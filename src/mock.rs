@@ -0,0 +1,314 @@
+// A local stand-in for the sapphire JSON-RPC nodes, answering VerifierLookupRequest and
+// KeyLookupRequest from an in-memory key directory with the same response shapes as the nodes.
+//
+// let nodes = MockNetwork::start(KeyDirectory::seeded(7, "partisia-twitter-mainnet", &["twitter|1"]), vec![NodeFaults::default(); 5]).await?;
+// let key = nodes.client().lookup_request(&VerifierId::twitter(1)).await?;
+
+use crate::TorusClient;
+use http::StatusCode;
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server,
+};
+use libsecp256k1::{PublicKey, SecretKey};
+use rand::{rngs::StdRng, SeedableRng};
+use serde_json::{json, Value};
+use sha3::{Digest, Keccak256};
+use std::{
+    collections::BTreeMap, convert::Infallible, io, net::SocketAddr, sync::Arc, time::Duration,
+};
+use tokio::task::JoinHandle;
+
+// what the nodes answer for a verifier id that has no key yet
+const ERROR_NOT_ASSIGNED: &str = "Verifier + VerifierID has not yet been assigned";
+const ERROR_KEY_NOT_FOUND: &str = "key not found";
+
+#[derive(Debug, Clone)]
+struct DirectoryEntry {
+    verifier: String,
+    verifier_id: String,
+    key_index: u64,
+    public_key: [u8; 65],
+}
+
+/// The keys the mock nodes hand out, by verifier and verifier id
+#[derive(Debug, Clone, Default)]
+pub struct KeyDirectory {
+    entries: Vec<DirectoryEntry>,
+}
+
+impl KeyDirectory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Assigns an uncompressed public key to a verifier id. Ids sharing a key share its index,
+    /// like the nodes do for linked accounts.
+    pub fn insert(&mut self, verifier: &str, verifier_id: &str, public_key: [u8; 65]) -> &mut Self {
+        let key_index = match self.entries.iter().find(|e| e.public_key == public_key) {
+            Some(e) => e.key_index,
+            None => self
+                .entries
+                .iter()
+                .map(|e| e.key_index + 1)
+                .max()
+                .unwrap_or(1),
+        };
+        self.entries.push(DirectoryEntry {
+            verifier: verifier.to_string(),
+            verifier_id: verifier_id.to_string(),
+            key_index,
+            public_key,
+        });
+        self
+    }
+
+    /// A fresh random key for each id, the same keys for the same seed
+    pub fn seeded(seed: u64, verifier: &str, verifier_ids: &[&str]) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut directory = Self::new();
+        for verifier_id in verifier_ids {
+            let secret = SecretKey::random(&mut rng);
+            directory.insert(
+                verifier,
+                verifier_id,
+                PublicKey::from_secret_key(&secret).serialize(),
+            );
+        }
+        directory
+    }
+
+    pub fn public_key(&self, verifier: &str, verifier_id: &str) -> Option<[u8; 65]> {
+        self.find(verifier, verifier_id).map(|e| e.public_key)
+    }
+
+    fn find(&self, verifier: &str, verifier_id: &str) -> Option<&DirectoryEntry> {
+        self.entries
+            .iter()
+            .find(|e| e.verifier == verifier && e.verifier_id == verifier_id)
+    }
+}
+
+/// How a mock node misbehaves
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NodeFaults {
+    /// delay before every answer
+    pub latency: Duration,
+    /// answer every request with this http status and an empty body
+    pub http_error: Option<StatusCode>,
+    /// answer with a body that is not json
+    pub malformed_json: bool,
+    /// answer lookups with a key, or key owners, that no other node agrees with
+    pub wrong_key: bool,
+}
+
+/// Mock nodes listening on localhost, stopped when dropped
+#[derive(Debug)]
+pub struct MockNetwork {
+    addrs: Vec<SocketAddr>,
+    servers: Vec<JoinHandle<()>>,
+}
+
+impl MockNetwork {
+    /// Starts one node per entry in `faults`, each on its own port
+    pub async fn start(directory: KeyDirectory, faults: Vec<NodeFaults>) -> io::Result<Self> {
+        let directory = Arc::new(directory);
+        let mut addrs = Vec::with_capacity(faults.len());
+        let mut servers = Vec::with_capacity(faults.len());
+        for (node, faults) in faults.into_iter().enumerate() {
+            let node = Arc::new(MockNode {
+                node,
+                directory: Arc::clone(&directory),
+                faults,
+            });
+            let make_svc = make_service_fn(move |_| {
+                let node = Arc::clone(&node);
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |req| {
+                        let node = Arc::clone(&node);
+                        async move { Ok::<_, Infallible>(node.handle(req).await) }
+                    }))
+                }
+            });
+            let server = Server::try_bind(&([127, 0, 0, 1], 0).into())
+                .map_err(|e| io::Error::new(io::ErrorKind::AddrNotAvailable, e))?
+                .serve(make_svc);
+            addrs.push(server.local_addr());
+            servers.push(tokio::spawn(async move {
+                let _ = server.await;
+            }));
+        }
+        Ok(Self { addrs, servers })
+    }
+
+    pub fn endpoints(&self) -> Vec<String> {
+        self.addrs
+            .iter()
+            .map(|addr| format!("http://{}/jrpc", addr))
+            .collect()
+    }
+
+    /// A client for these nodes with a majority threshold
+    pub fn client(&self) -> TorusClient {
+        TorusClient::new(self.endpoints())
+    }
+}
+
+impl Drop for MockNetwork {
+    fn drop(&mut self) {
+        for server in &self.servers {
+            server.abort();
+        }
+    }
+}
+
+struct MockNode {
+    node: usize,
+    directory: Arc<KeyDirectory>,
+    faults: NodeFaults,
+}
+
+impl MockNode {
+    async fn handle(&self, req: Request<Body>) -> Response<Body> {
+        if !self.faults.latency.is_zero() {
+            tokio::time::sleep(self.faults.latency).await;
+        }
+        if let Some(status) = self.faults.http_error {
+            let mut response = Response::new(Body::empty());
+            *response.status_mut() = status;
+            return response;
+        }
+        if self.faults.malformed_json {
+            return Response::new(Body::from("{\"jsonrpc\": \"2.0\", \"result\": {"));
+        }
+
+        let body = match hyper::body::to_bytes(req.into_body()).await {
+            Ok(body) => body,
+            Err(_) => return Self::rpc_response(Value::Null, Err((-32700, "Parse error", ""))),
+        };
+        let request: Value = match serde_json::from_slice(&body) {
+            Ok(request) => request,
+            Err(_) => return Self::rpc_response(Value::Null, Err((-32700, "Parse error", ""))),
+        };
+        let id = request["id"].clone();
+        let params = &request["params"];
+        let result = match request["method"].as_str() {
+            Some("VerifierLookupRequest") => self.verifier_lookup(params),
+            Some("KeyLookupRequest") => self.key_lookup(params),
+            _ => Err((-32601, "Method not found", "")),
+        };
+        Self::rpc_response(id, result)
+    }
+
+    fn rpc_response(id: Value, result: Result<Value, (i64, &str, &str)>) -> Response<Body> {
+        let body = match result {
+            Ok(result) => json!({"jsonrpc": "2.0", "result": result, "id": id}),
+            Err((code, message, data)) => json!({
+                "jsonrpc": "2.0",
+                "error": {"code": code, "message": message, "data": data},
+                "id": id
+            }),
+        };
+        Response::new(Body::from(body.to_string()))
+    }
+
+    fn verifier_lookup(&self, params: &Value) -> Result<Value, (i64, &'static str, &'static str)> {
+        let verifier = params["verifier"].as_str().unwrap_or_default();
+        let verifier_id = params["verifier_id"].as_str().unwrap_or_default();
+        let entry = self.directory.find(verifier, verifier_id).ok_or((
+            -32602,
+            "Input error",
+            ERROR_NOT_ASSIGNED,
+        ))?;
+
+        let public_key = if self.faults.wrong_key {
+            forged_key(self.node, verifier_id)
+        } else {
+            entry.public_key
+        };
+        Ok(json!({"keys": [{
+            "key_index": format!("{:x}", entry.key_index),
+            "pub_key_X": node_hex(&public_key[1..33]),
+            "pub_key_Y": node_hex(&public_key[33..]),
+            "address": address(&public_key),
+        }]}))
+    }
+
+    fn key_lookup(&self, params: &Value) -> Result<Value, (i64, &'static str, &'static str)> {
+        let parse = |coordinate: &Value| -> Option<[u8; 32]> {
+            let hex = coordinate.as_str()?;
+            let bytes = hex::decode(format!("{:0>64}", hex)).ok()?;
+            bytes.try_into().ok()
+        };
+        let not_found = (-32602, "Input error", ERROR_KEY_NOT_FOUND);
+        let x = parse(&params["pub_key_X"]).ok_or(not_found)?;
+        let y = parse(&params["pub_key_Y"]).ok_or(not_found)?;
+
+        let owners: Vec<&DirectoryEntry> = self
+            .directory
+            .entries
+            .iter()
+            .filter(|e| e.public_key[1..33] == x && e.public_key[33..] == y)
+            .collect();
+        let first = owners.first().ok_or(not_found)?;
+
+        let mut verifiers: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for owner in &owners {
+            let verifier_id = if self.faults.wrong_key {
+                format!("{}-{}", owner.verifier_id, self.node)
+            } else {
+                owner.verifier_id.clone()
+            };
+            verifiers
+                .entry(&owner.verifier)
+                .or_default()
+                .push(verifier_id);
+        }
+        Ok(json!({
+            "Index": format!("{:x}", first.key_index),
+            "PublicKey": {
+                "X": node_hex(&first.public_key[1..33]),
+                "Y": node_hex(&first.public_key[33..]),
+            },
+            "Threshold": 1,
+            "Verifiers": verifiers,
+        }))
+    }
+}
+
+// the nodes print coordinates as big integers, without leading zeros
+fn node_hex(bytes: &[u8]) -> String {
+    let hex = hex::encode(bytes);
+    let trimmed = hex.trim_start_matches('0');
+    if trimmed.is_empty() {
+        "0".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+// the checksummed ethereum address of an uncompressed public key
+fn address(public_key: &[u8; 65]) -> String {
+    let hash = Keccak256::digest(&public_key[1..]);
+    let address = hex::encode(&hash[12..]);
+    let checksum = hex::encode(Keccak256::digest(address.as_bytes()));
+    let checksummed: String = address
+        .chars()
+        .zip(checksum.chars())
+        .map(|(c, h)| {
+            if h.to_digit(16).unwrap_or(0) >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect();
+    format!("0x{}", checksummed)
+}
+
+// a valid key that differs per node and verifier id, so forged answers do not agree with each other
+fn forged_key(node: usize, verifier_id: &str) -> [u8; 65] {
+    let seed = crate::sha256_hash(format!("forged-{}-{}", node, verifier_id).as_bytes());
+    let mut rng = StdRng::from_seed(seed);
+    PublicKey::from_secret_key(&SecretKey::random(&mut rng)).serialize()
+}
@@ -0,0 +1,139 @@
+use crate::Verifier;
+
+// Node lists as published by @toruslabs/fetch-node-details for each network
+
+const SAPPHIRE_MAINNET_ENDPOINTS: [&str; 5] = [
+    "https://sapphire-1.auth.network/sss/mainnet/jrpc",
+    "https://sapphire-2.auth.network/sss/mainnet/jrpc",
+    "https://sapphire-3.auth.network/sss/mainnet/jrpc",
+    "https://sapphire-4.auth.network/sss/mainnet/jrpc",
+    "https://sapphire-5.auth.network/sss/mainnet/jrpc",
+];
+
+const SAPPHIRE_DEVNET_ENDPOINTS: [&str; 5] = [
+    "https://sapphire-dev-2-1.authnetwork.dev/sss/jrpc",
+    "https://sapphire-dev-2-2.authnetwork.dev/sss/jrpc",
+    "https://sapphire-dev-2-3.authnetwork.dev/sss/jrpc",
+    "https://sapphire-dev-2-4.authnetwork.dev/sss/jrpc",
+    "https://sapphire-dev-2-5.authnetwork.dev/sss/jrpc",
+];
+
+const LEGACY_MAINNET_ENDPOINTS: [&str; 9] = [
+    "https://torus-19.torusnode.com/jrpc",
+    "https://torus-node.ens.domains/jrpc",
+    "https://torus-node.matic.network/jrpc",
+    "https://torus.zilliqa.network/jrpc",
+    "https://torus-mainnet.cosmos.network/jrpc",
+    "https://torus2.etherscan.com/jrpc",
+    "https://torus-node-v2.skalelabs.com/jrpc",
+    "https://torus-node.binancex.dev/jrpc",
+    "https://torusnode.ont.io/jrpc",
+];
+
+#[cfg(test)]
+pub(crate) const TORUS_ENDPOINTS: [&str; 5] = SAPPHIRE_MAINNET_ENDPOINTS;
+
+const VERIFIER_TWITTER: &str = "partisia-twitter-mainnet";
+const VERIFIER_DISCORD: &str = "partisia-discord";
+const VERIFIER_APPLE: &str = "parti-apple";
+
+/// The verifier names registered for Partisia on a network
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifierNames {
+    pub twitter: String,
+    pub discord: String,
+    pub apple: String,
+}
+
+impl Default for VerifierNames {
+    fn default() -> Self {
+        Self {
+            twitter: VERIFIER_TWITTER.to_string(),
+            discord: VERIFIER_DISCORD.to_string(),
+            apple: VERIFIER_APPLE.to_string(),
+        }
+    }
+}
+
+impl VerifierNames {
+    /// The name the nodes know `verifier` by
+    pub fn name<'a>(&'a self, verifier: &'a Verifier) -> &'a str {
+        match verifier {
+            Verifier::Twitter => &self.twitter,
+            Verifier::Discord => &self.discord,
+            Verifier::Apple => &self.apple,
+            Verifier::Custom(name) => name,
+        }
+    }
+
+    /// The verifier the nodes know by `name`
+    pub fn verifier(&self, name: &str) -> Verifier {
+        if name == self.twitter {
+            Verifier::Twitter
+        } else if name == self.discord {
+            Verifier::Discord
+        } else if name == self.apple {
+            Verifier::Apple
+        } else {
+            Verifier::Custom(name.to_string())
+        }
+    }
+}
+
+/// The Torus node set a client talks to. The presets use the Partisia verifier
+/// names and a majority threshold; use `Custom` for anything else.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum TorusNetwork {
+    #[default]
+    SapphireMainnet,
+    SapphireDevnet,
+    LegacyMainnet,
+    Custom {
+        endpoints: Vec<String>,
+        threshold: usize,
+        verifiers: VerifierNames,
+    },
+}
+
+impl TorusNetwork {
+    /// A custom node set with a majority threshold and the Partisia verifier names
+    pub fn custom<I, S>(endpoints: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let endpoints: Vec<String> = endpoints.into_iter().map(Into::into).collect();
+        TorusNetwork::Custom {
+            threshold: endpoints.len() / 2 + 1,
+            endpoints,
+            verifiers: VerifierNames::default(),
+        }
+    }
+
+    pub fn endpoints(&self) -> Vec<String> {
+        let preset: &[&str] = match self {
+            TorusNetwork::SapphireMainnet => &SAPPHIRE_MAINNET_ENDPOINTS,
+            TorusNetwork::SapphireDevnet => &SAPPHIRE_DEVNET_ENDPOINTS,
+            TorusNetwork::LegacyMainnet => &LEGACY_MAINNET_ENDPOINTS,
+            TorusNetwork::Custom { endpoints, .. } => return endpoints.clone(),
+        };
+        preset.iter().map(|s| s.to_string()).collect()
+    }
+
+    /// The number of nodes that have to agree on a result
+    pub fn threshold(&self) -> usize {
+        match self {
+            TorusNetwork::SapphireMainnet | TorusNetwork::SapphireDevnet => 3,
+            TorusNetwork::LegacyMainnet => 5,
+            TorusNetwork::Custom { threshold, .. } => *threshold,
+        }
+    }
+
+    pub fn verifiers(&self) -> VerifierNames {
+        match self {
+            TorusNetwork::Custom { verifiers, .. } => verifiers.clone(),
+            // devnet uses the mainnet verifier names
+            _ => VerifierNames::default(),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{timer, Result, TorusError};
+#[cfg(any(feature = "tokio", feature = "isahc"))]
+use http::header::{HeaderMap, RETRY_AFTER};
+use rand::Rng;
+use std::{future::Future, time::Duration};
+
+/// How often a failing node is called again before its error counts towards consensus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// calls per node including the first one, 1 disables retries
+    pub max_attempts: u32,
+    /// the delay before the first retry, doubled for each retry after that
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    // the delay a node asked for with Retry-After, otherwise exponential backoff with jitter up to
+    // max_delay
+    fn delay(&self, retry: u32, error: &TorusError) -> Duration {
+        if let TorusError::HttpStatus {
+            retry_after: Some(retry_after),
+            ..
+        } = error
+        {
+            return *retry_after;
+        }
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+        // somewhere between half and all of the backoff so nodes are not retried in lockstep
+        rand::thread_rng().gen_range(backoff / 2..=backoff)
+    }
+}
+
+impl TorusError {
+    // errors that may go away if the node is asked again
+    fn is_transient(&self) -> bool {
+        match self {
+            TorusError::Transport(_) | TorusError::Timeout => true,
+            TorusError::HttpStatus { status, .. } => matches!(status, 429 | 500 | 502 | 503 | 504),
+            _ => false,
+        }
+    }
+}
+
+// the delay a 429 or 503 response asks for, as seconds or an http date
+#[cfg(any(feature = "tokio", feature = "isahc"))]
+pub(crate) fn retry_after(status: u16, headers: &HeaderMap) -> Option<Duration> {
+    if status != 429 && status != 503 {
+        return None;
+    }
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(std::time::SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+// call a node until it succeeds, fails permanently, runs out of attempts or the next retry would
+// start after `budget`, returning the number of attempts made
+pub(crate) async fn with_retries<F, Fut, T>(
+    policy: &RetryPolicy,
+    budget: Duration,
+    mut call: F,
+) -> (Result<T>, u32)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let start = timer::Instant::now();
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let result = call().await;
+        match &result {
+            Err(e) if e.is_transient() && attempts < policy.max_attempts => {
+                let delay = policy.delay(attempts - 1, e);
+                if start.elapsed() + delay >= budget {
+                    return (result, attempts);
+                }
+                timer::sleep(delay).await;
+            }
+            _ => return (result, attempts),
+        }
+    }
+}
@@ -23,9 +23,10 @@ fn deserialize_verifier_lookup_request() {
       },
       "id": 10
     }"#;
-    let json: JsonRpc<TorusKeys> = serde_json::from_str(res_json).unwrap();
+    let json: JsonRpcResponse = serde_json::from_str(res_json).unwrap();
+    let result: TorusKeys = serde_json::from_value(json.into_result(&json!(10)).unwrap()).unwrap();
     assert_eq!(
-        json.result.keys[0].pub_key_x,
+        result.keys[0].pub_key_x,
         "436676f1c06a11f805a92d5d02a5789296c562d1aeb8e72d6318760f61cdcbf"
     );
 }
@@ -54,17 +55,19 @@ fn deserialize_key_lookup_request() {
       },
       "id": 10
     }"#;
-    let json: JsonRpc<TorusLookup> = serde_json::from_str(res_json).unwrap();
+    let json: JsonRpcResponse = serde_json::from_str(res_json).unwrap();
+    let result: TorusLookup =
+        serde_json::from_value(json.into_result(&json!(10)).unwrap()).unwrap();
     assert_eq!(
-        json.result.public_key.x,
+        result.public_key.x,
         "436676f1c06a11f805a92d5d02a5789296c562d1aeb8e72d6318760f61cdcbf"
     );
     assert_eq!(
-        json.result.public_key.y,
+        result.public_key.y,
         "afd563755d627d1ae4021d60863acca0c3bf4e5d8f5ce24c91e55ebbf5b263b0"
     );
 
-    let ary_ids = json.result.verifiers.partisia.unwrap();
+    let ary_ids = &result.verifiers.0["partisia-twitter-mainnet"];
     assert_eq!(ary_ids.len(), 1);
     assert_eq!(ary_ids[0], "twitter|1415723267256639488");
     assert_eq!(
@@ -81,11 +84,1282 @@ fn num() {
 }
 
 #[tokio::test]
-async fn rpc_fetch_consensus() {
-    let j = json!({"jsonrpc":"2.0","id":10,"method":"VerifierLookupRequest","params":{"verifier":"partisia-twitter-mainnet", "verifier_id":"twitter|1415723267256639488"}});
-    let x: TorusKeys = consensus_multi_thread::rpc_with_consensus(&j)
+async fn rpc_no_endpoints() {
+    let client = TorusClient::new(Vec::<String>::new());
+    let err = client
+        .lookup_request(&VerifierId::twitter(1415723267256639488))
+        .await
+        .unwrap_err();
+    assert_eq!(err, TorusError::NoEndpoints);
+}
+
+#[test]
+fn network_presets() {
+    let mainnet = TorusClient::default();
+    assert_eq!(mainnet.network(), &TorusNetwork::SapphireMainnet);
+    assert_eq!(mainnet.endpoints().len(), 5);
+    assert_eq!(mainnet.threshold(), 3);
+
+    let legacy = TorusClient::with_network(TorusNetwork::LegacyMainnet);
+    assert_eq!(legacy.endpoints()[0], "https://torus-19.torusnode.com/jrpc");
+    assert_eq!(legacy.endpoints().len(), 9);
+    assert_eq!(legacy.threshold(), 5);
+
+    let custom = TorusClient::new(["http://localhost:1", "http://localhost:2"]);
+    assert_eq!(custom.threshold(), 2);
+}
+
+#[tokio::test]
+async fn rpc_threshold_exceeds_endpoints() {
+    let client = TorusClient::with_network(TorusNetwork::Custom {
+        endpoints: vec!["http://localhost:1".to_string()],
+        threshold: 2,
+        verifiers: VerifierNames::default(),
+    });
+    let err = client
+        .lookup_request(&VerifierId::twitter(1415723267256639488))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TorusError::InvalidThreshold {
+            required: 2,
+            nodes: 1
+        }
+    );
+}
+
+#[test]
+fn consensus_policy_required() {
+    assert_eq!(ConsensusPolicy::Network.required(3, 5).unwrap(), 3);
+    assert_eq!(ConsensusPolicy::Majority.required(3, 9).unwrap(), 5);
+    assert_eq!(ConsensusPolicy::KOfN(2).required(3, 5).unwrap(), 2);
+    assert_eq!(ConsensusPolicy::Unanimous.required(3, 5).unwrap(), 5);
+    assert!(ConsensusPolicy::KOfN(0).required(3, 5).is_err());
+    assert!(ConsensusPolicy::KOfN(6).required(3, 5).is_err());
+}
+
+// a node whose result is its own canonical form
+fn answer(result: Result<Value>) -> Option<NodeAnswer> {
+    Some(NodeAnswer {
+        result: result.map(|value| NodeValue {
+            key: value.to_string().into_bytes(),
+            value,
+        }),
+        latency: Duration::from_millis(20),
+        attempts: 1,
+    })
+}
+
+#[test]
+fn tally_fails_fast() {
+    let results: ConsensusResults = vec![
+        answer(Ok(json!(1))),
+        answer(Ok(json!(2))),
+        answer(Err(TorusError::Timeout)),
+        answer(Err(TorusError::HttpStatus {
+            status: 502,
+            retry_after: None,
+        })),
+        None,
+    ];
+    // the pending node could only bring one group to 2 votes
+    match tally(&results, 3) {
+        Tally::Impossible(TorusError::NoConsensus { groups, errors, .. }) => {
+            assert_eq!(groups.len(), 2);
+            assert_eq!(groups[0].nodes, vec![0]);
+            assert_eq!(groups[1].nodes, vec![1]);
+            assert_eq!(
+                errors,
+                vec![
+                    NodeFailure {
+                        node: 2,
+                        error: TorusError::Timeout
+                    },
+                    NodeFailure {
+                        node: 3,
+                        error: TorusError::HttpStatus {
+                            status: 502,
+                            retry_after: None,
+                        }
+                    },
+                ]
+            );
+        }
+        _ => panic!("expected consensus to be impossible"),
+    }
+    assert!(matches!(tally(&results, 2), Tally::Pending));
+
+    let results: ConsensusResults = vec![answer(Ok(json!(1))), None, answer(Ok(json!(1)))];
+    assert!(matches!(tally(&results, 2), Tally::Agreed(v) if *v == json!(1)));
+
+    let results: ConsensusResults = (0..3).map(|_| answer(Err(TorusError::Timeout))).collect();
+    match tally(&results, 2) {
+        Tally::Impossible(e) => assert_eq!(
+            e.to_string(),
+            "all nodes failed: node 0: timed out; node 1: timed out; node 2: timed out"
+        ),
+        _ => panic!("expected every node to have failed"),
+    }
+}
+
+#[test]
+fn jsonrpc_responses() {
+    let parse = |body: Value| {
+        serde_json::from_value::<JsonRpcResponse>(body)
+            .unwrap()
+            .into_result(&json!(7))
+    };
+    assert_eq!(
+        parse(json!({"jsonrpc": "2.0", "id": 7, "result": {"keys": []}})),
+        Ok(json!({"keys": []}))
+    );
+    assert_eq!(
+        parse(
+            json!({"jsonrpc": "2.0", "id": 7, "error": {"code": -32601, "message": "Method not found"}})
+        ),
+        Err(TorusError::Rpc {
+            code: -32601,
+            message: "Method not found".to_string(),
+            data: None
+        })
+    );
+    assert_eq!(
+        parse(json!({"jsonrpc": "2.0", "id": 7, "error": {
+            "code": -32602,
+            "message": "Input error",
+            "data": "Verifier + VerifierID has not yet been assigned"
+        }})),
+        Err(TorusError::KeyNotFound)
+    );
+    // an answer to some other request
+    assert!(matches!(
+        parse(json!({"jsonrpc": "2.0", "id": 10, "result": {"keys": []}})),
+        Err(TorusError::MalformedResponse(_))
+    ));
+
+    // enough nodes finding nothing decide the call, a failing node does not count towards it
+    let results: ConsensusResults = vec![
+        answer(Err(TorusError::KeyNotFound)),
+        answer(Err(TorusError::Timeout)),
+        answer(Err(TorusError::KeyNotFound)),
+    ];
+    assert!(matches!(
+        tally(&results, 2),
+        Tally::Impossible(TorusError::KeyNotFound)
+    ));
+    assert!(matches!(
+        tally(&results, 3),
+        Tally::Impossible(TorusError::AllNodesFailed(_))
+    ));
+}
+
+#[test]
+fn consensus_report() {
+    let results: ConsensusResults = vec![
+        answer(Ok(json!(1))),
+        answer(Ok(json!(2))),
+        answer(Ok(json!(1))),
+        answer(Err(TorusError::Timeout)),
+        None,
+    ];
+    let endpoints: Vec<String> = (0..5).map(|i| format!("http://node-{}", i)).collect();
+    let report = report(&results, &endpoints, 2);
+    assert_eq!(report.required, 2);
+
+    let outcomes: Vec<_> = report.nodes.iter().map(|n| n.outcome.clone()).collect();
+    assert_eq!(
+        outcomes,
+        vec![
+            NodeOutcome::Agreed,
+            NodeOutcome::Disagreed,
+            NodeOutcome::Agreed,
+            NodeOutcome::Error(TorusError::Timeout),
+            NodeOutcome::Cancelled,
+        ]
+    );
+    let groups: Vec<_> = report.nodes.iter().map(|n| n.group).collect();
+    assert_eq!(groups, vec![Some(0), Some(1), Some(0), None, None]);
+    assert_eq!(report.nodes[4].endpoint, "http://node-4");
+    assert_eq!(report.nodes[4].latency, None);
+    assert_eq!(report.nodes[0].latency, Some(Duration::from_millis(20)));
+}
+
+#[test]
+fn audit_agreement_matrix() {
+    let results: ConsensusResults = vec![
+        answer(Ok(json!(7))),
+        answer(Ok(json!(8))),
+        answer(Ok(json!(7))),
+        answer(Err(TorusError::HttpStatus {
+            status: 503,
+            retry_after: None,
+        })),
+    ];
+    let endpoints: Vec<String> = (0..4).map(|i| format!("http://node-{}", i)).collect();
+    let audited: Audit<Option<u32>> =
+        audit(&results, &endpoints, 2, ConsensusPolicy::KOfN(2)).unwrap();
+    assert_eq!(audited.value, Some(Some(7)));
+    assert_eq!(
+        audited.agreement,
+        vec![
+            vec![true, false, true, false],
+            vec![false, true, false, false],
+            vec![true, false, true, false],
+            vec![false, false, false, false],
+        ]
+    );
+    assert_eq!(audited.dissenting_nodes(), vec![1, 3]);
+
+    let audited: Audit<Option<u32>> =
+        audit(&results, &endpoints, 3, ConsensusPolicy::KOfN(3)).unwrap();
+    assert_eq!(audited.value, None);
+}
+
+#[test]
+fn audit_agreed_not_found() {
+    let results: ConsensusResults = vec![
+        answer(Err(TorusError::KeyNotFound)),
+        answer(Err(TorusError::KeyNotFound)),
+        answer(Err(TorusError::Timeout)),
+    ];
+    let endpoints: Vec<String> = (0..3).map(|i| format!("http://node-{}", i)).collect();
+    let audited: Audit<Option<u32>> =
+        audit(&results, &endpoints, 2, ConsensusPolicy::KOfN(2)).unwrap();
+    // an unassigned id is an answer, not a missing majority
+    assert_eq!(audited.value, Some(None));
+    assert_eq!(audited.dissenting_nodes(), vec![2]);
+    assert_eq!(
+        audited.agreement,
+        vec![
+            vec![true, true, false],
+            vec![true, true, false],
+            vec![false, false, false],
+        ]
+    );
+
+    let audited: Audit<Option<u32>> =
+        audit(&results, &endpoints, 3, ConsensusPolicy::KOfN(3)).unwrap();
+    assert_eq!(audited.value, None);
+    assert_eq!(audited.dissenting_nodes(), vec![0, 1, 2]);
+}
+
+#[cfg(any(feature = "tokio", feature = "isahc"))]
+#[tokio::test]
+async fn rpc_lookup_deadline_names_pending_nodes() {
+    // accepts connections but never answers
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let mut held = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            held.push(socket);
+        }
+    });
+
+    let endpoints: Vec<String> = (0..3).map(|i| format!("http://{}/{}", addr, i)).collect();
+    let client = TorusClient::new(endpoints.clone()).with_deadlines(Deadlines {
+        lookup: Duration::from_millis(200),
+        node: Duration::from_secs(10),
+        ..Deadlines::default()
+    });
+    let err = client
+        .lookup_request(&VerifierId::twitter(1415723267256639488))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TorusError::DeadlineExceeded {
+            deadline: Duration::from_millis(200),
+            pending: endpoints,
+        }
+    );
+}
+
+#[cfg(feature = "multi_thread")]
+#[test]
+fn lookups_are_send() {
+    fn assert_send<F: std::future::Future + Send>(_: F) {}
+    let client = TorusClient::default();
+    assert_send(client.lookup_request(&VerifierId::twitter(1)));
+    assert_send(
+        client.lookup_request_with_report(&VerifierId::twitter(1), ConsensusPolicy::Majority),
+    );
+    assert_send(client.key_lookup_request(&[0; 32], &[0; 32]));
+    assert_send(multi_thread::lookup_request(&VerifierId::twitter(1)));
+}
+
+#[cfg(any(feature = "tokio", feature = "isahc"))]
+#[test]
+fn retry_after_header() {
+    let mut headers = http::HeaderMap::new();
+    headers.insert(
+        http::header::RETRY_AFTER,
+        http::HeaderValue::from_static("2"),
+    );
+    assert_eq!(retry_after(429, &headers), Some(Duration::from_secs(2)));
+    assert_eq!(retry_after(503, &headers), Some(Duration::from_secs(2)));
+    assert_eq!(retry_after(502, &headers), None);
+
+    headers.insert(
+        http::header::RETRY_AFTER,
+        http::HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+    );
+    assert_eq!(retry_after(503, &headers), Some(Duration::ZERO));
+}
+
+#[cfg(any(feature = "tokio", feature = "isahc"))]
+const VERIFIER_LOOKUP_RESPONSE: &str = r#"{
+  "jsonrpc": "2.0",
+  "result": {
+    "keys": [
+      {
+        "key_index": "14745a",
+        "pub_key_X": "436676f1c06a11f805a92d5d02a5789296c562d1aeb8e72d6318760f61cdcbf",
+        "pub_key_Y": "afd563755d627d1ae4021d60863acca0c3bf4e5d8f5ce24c91e55ebbf5b263b0",
+        "address": "0xC9F0af3d1D6089992C0041902D846c4b448311F2"
+      }
+    ]
+  },
+  "id": 10
+}"#;
+
+// nodes that answer the first call on each path with a 503 and the lookup after that
+#[cfg(any(feature = "tokio", feature = "isahc"))]
+async fn flaky_nodes(count: usize) -> Vec<String> {
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Request, Response, Server};
+    use std::{collections::HashMap, convert::Infallible, sync::Mutex};
+
+    let calls: Arc<Mutex<HashMap<String, usize>>> = Arc::default();
+    let make_svc = make_service_fn(move |_| {
+        let calls = Arc::clone(&calls);
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                let first = {
+                    let mut calls = calls.lock().unwrap();
+                    let count = calls.entry(req.uri().path().to_string()).or_default();
+                    *count += 1;
+                    *count == 1
+                };
+                async move {
+                    let res = if first {
+                        Response::builder()
+                            .status(503)
+                            .header("Retry-After", "0")
+                            .body(Body::empty())
+                    } else {
+                        // answer with the id of the request
+                        let body = hyper::body::to_bytes(req.into_body()).await.unwrap();
+                        let request: Value = serde_json::from_slice(&body).unwrap();
+                        let mut response: Value =
+                            serde_json::from_str(VERIFIER_LOOKUP_RESPONSE).unwrap();
+                        response["id"] = request["id"].clone();
+                        Response::builder().body(Body::from(response.to_string()))
+                    };
+                    Ok::<_, Infallible>(res.unwrap())
+                }
+            }))
+        }
+    });
+    let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_svc);
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    (0..count)
+        .map(|i| format!("http://{}/{}", addr, i))
+        .collect()
+}
+
+#[cfg(any(feature = "tokio", feature = "isahc"))]
+#[tokio::test]
+async fn rpc_retries_unavailable_nodes() {
+    let client = TorusClient::new(flaky_nodes(3).await);
+    let (key, report) = client
+        .lookup_request_with_report(
+            &VerifierId::twitter(1415723267256639488),
+            ConsensusPolicy::Unanimous,
+        )
+        .await;
+    assert!(key.unwrap().value.is_some());
+    let attempts: Vec<_> = report.nodes.iter().map(|n| n.attempts).collect();
+    assert_eq!(attempts, vec![Some(2); 3]);
+
+    // without retries the 503s are final
+    let client = TorusClient::new(flaky_nodes(3).await).with_retry_policy(RetryPolicy::none());
+    let err = client
+        .lookup_request(&VerifierId::twitter(1415723267256639488))
+        .await
+        .unwrap_err();
+    match err {
+        // two 503s already make a majority impossible
+        TorusError::NoConsensus { groups, errors, .. } => {
+            assert!(groups.is_empty());
+            assert_eq!(errors.len(), 2);
+            assert_eq!(
+                errors[0].error,
+                TorusError::HttpStatus {
+                    status: 503,
+                    retry_after: Some(Duration::ZERO)
+                }
+            );
+        }
+        e => panic!("unexpected error {}", e),
+    }
+}
+
+#[tokio::test]
+async fn with_deadlines_keeps_transport() {
+    let keys = fake_keys("436676f1c06a11f805a92d5d02a5789296c562d1aeb8e72d6318760f61cdcbf");
+    let client = FakeNodes::new(vec![Ok(keys.clone()), Ok(keys.clone()), Ok(keys)])
+        .client()
+        .with_deadlines(Deadlines {
+            connect: Duration::from_millis(100),
+            ..Deadlines::default()
+        });
+    // still answered by the fake nodes rather than over http
+    let key = client
+        .lookup_request(&VerifierId::twitter(1415723267256639488))
+        .await
+        .unwrap();
+    assert!(key.is_some());
+}
+
+#[tokio::test]
+async fn rpc_retry_after_stays_within_deadlines() {
+    let client = |retry: RetryPolicy, retry_after: Duration| {
+        let unavailable = || {
+            Err(TorusError::HttpStatus {
+                status: 503,
+                retry_after: Some(retry_after),
+            })
+        };
+        let endpoints: Vec<String> = (0..3).map(|i| format!("fake://node-{}", i)).collect();
+        TorusClient::new(endpoints)
+            .with_deadlines(Deadlines {
+                lookup: Duration::from_secs(5),
+                ..Deadlines::default()
+            })
+            .with_transport(FakeNodes::new(vec![
+                unavailable(),
+                unavailable(),
+                unavailable(),
+            ]))
+            .with_retry_policy(retry)
+    };
+
+    // the nodes are retried after the delay they ask for, even one longer than max_delay
+    let start = Instant::now();
+    let (key, report) = client(
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(10),
+        },
+        Duration::from_millis(200),
+    )
+    .lookup_request_with_report(
+        &VerifierId::twitter(1415723267256639488),
+        ConsensusPolicy::Majority,
+    )
+    .await;
+    assert!(start.elapsed() >= Duration::from_millis(400));
+    assert!(!matches!(key, Err(TorusError::DeadlineExceeded { .. })));
+    assert!(report.nodes.iter().any(|n| n.attempts == Some(3)));
+
+    // a retry that would start after the lookup deadline is not made, the node's 503 stands
+    let start = Instant::now();
+    let (key, report) = client(RetryPolicy::default(), Duration::from_secs(3600))
+        .lookup_request_with_report(
+            &VerifierId::twitter(1415723267256639488),
+            ConsensusPolicy::Majority,
+        )
+        .await;
+    assert!(start.elapsed() < Duration::from_secs(1));
+    assert!(!matches!(key, Err(TorusError::DeadlineExceeded { .. })));
+    assert!(report.nodes.iter().all(|n| n.attempts <= Some(1)));
+}
+
+#[cfg(feature = "isahc")]
+#[tokio::test]
+async fn rpc_isahc_transport() {
+    let client = TorusClient::new(flaky_nodes(3).await)
+        .with_transport(IsahcTransport::new(Duration::from_secs(1)));
+    let consensus = client
+        .lookup_request_with_policy(
+            &VerifierId::twitter(1415723267256639488),
+            ConsensusPolicy::Unanimous,
+        )
+        .await
+        .unwrap();
+    assert!(consensus.value.is_some());
+}
+
+// the body of a node's response to `request`
+fn rpc_response(request: &Value, result: Value) -> Vec<u8> {
+    json!({"jsonrpc": "2.0", "id": request["id"], "result": result})
+        .to_string()
+        .into_bytes()
+}
+
+// in-memory nodes that answer every request with a fixed result
+struct FakeNodes {
+    results: Vec<Result<Value>>,
+}
+
+impl FakeNodes {
+    fn new(results: Vec<Result<Value>>) -> Self {
+        Self { results }
+    }
+
+    fn client(self) -> TorusClient {
+        let endpoints: Vec<String> = (0..self.results.len())
+            .map(|i| format!("fake://node-{}", i))
+            .collect();
+        TorusClient::new(endpoints).with_transport(self)
+    }
+}
+
+impl TorusTransport for FakeNodes {
+    fn send<'a>(
+        &'a self,
+        node: usize,
+        _endpoint: &'a str,
+        request: &'a Value,
+    ) -> futures::future::BoxFuture<'a, Result<Vec<u8>>> {
+        let body = self.results[node]
+            .clone()
+            .map(|result| rpc_response(request, result));
+        Box::pin(futures::future::ready(body))
+    }
+}
+
+// another point on the curve with the same y as the fake keys, x times a cube root of unity
+const OTHER_PUB_KEY_X: &str = "11bdd3ce48b9bc40ad09c557b32f9e66953dc074e704e75b99bbbc20f4e72f92";
+
+fn fake_keys(pub_key_x: &str) -> Value {
+    json!({"keys": [{
+        "key_index": "14745a",
+        "pub_key_X": pub_key_x,
+        "pub_key_Y": "afd563755d627d1ae4021d60863acca0c3bf4e5d8f5ce24c91e55ebbf5b263b0",
+        "address": "0xC9F0af3d1D6089992C0041902D846c4b448311F2"
+    }]})
+}
+
+// in-memory nodes that all answer a verifier lookup with the keys `route` gives for its verifier id
+struct RoutedNodes {
+    route: fn(&str) -> Result<Value>,
+}
+
+impl TorusTransport for RoutedNodes {
+    fn send<'a>(
+        &'a self,
+        _node: usize,
+        _endpoint: &'a str,
+        request: &'a Value,
+    ) -> futures::future::BoxFuture<'a, Result<Vec<u8>>> {
+        let body = (self.route)(
+            request["params"]["verifier_id"]
+                .as_str()
+                .unwrap_or_default(),
+        )
+        .map(|result| rpc_response(request, result));
+        Box::pin(futures::future::ready(body))
+    }
+}
+
+#[tokio::test]
+async fn lookup_twitter_id_formats() {
+    let lookup = |route: fn(&str) -> Result<Value>| async move {
+        let endpoints: Vec<String> = (0..3).map(|i| format!("fake://node-{}", i)).collect();
+        TorusClient::new(endpoints)
+            .with_transport(RoutedNodes { route })
+            .with_retry_policy(RetryPolicy::none())
+            .lookup_request_any_format(&VerifierId::twitter(200985979), ConsensusPolicy::Majority)
+            .await
+    };
+    let found = lookup(|id| match id {
+        "200985979" => Ok(fake_keys(
+            "436676f1c06a11f805a92d5d02a5789296c562d1aeb8e72d6318760f61cdcbf",
+        )),
+        _ => Err(TorusError::KeyNotFound),
+    })
+    .await
+    .unwrap();
+    assert_eq!(found.format, Some(IdFormat::Bare));
+    assert!(found.key.is_some());
+
+    // the prefixed format might have resolved to another key, so its failure is not skipped
+    let failed = lookup(|id| match id {
+        "200985979" => Ok(fake_keys(
+            "436676f1c06a11f805a92d5d02a5789296c562d1aeb8e72d6318760f61cdcbf",
+        )),
+        _ => Err(TorusError::HttpStatus {
+            status: 502,
+            retry_after: None,
+        }),
+    })
+    .await;
+    assert!(matches!(
+        failed,
+        Err(TorusError::NoConsensus { .. } | TorusError::AllNodesFailed(_))
+    ));
+
+    let found = lookup(|id| match id {
+        "twitter|200985979" | "200985979" => Ok(fake_keys(
+            "436676f1c06a11f805a92d5d02a5789296c562d1aeb8e72d6318760f61cdcbf",
+        )),
+        _ => unreachable!(),
+    })
+    .await
+    .unwrap();
+    assert_eq!(found.format, Some(IdFormat::Prefixed));
+
+    let conflict = lookup(|id| match id {
+        "twitter|200985979" => Ok(fake_keys(
+            "436676f1c06a11f805a92d5d02a5789296c562d1aeb8e72d6318760f61cdcbf",
+        )),
+        _ => Ok(fake_keys(OTHER_PUB_KEY_X)),
+    })
+    .await;
+    assert_eq!(
+        conflict,
+        Err(TorusError::FormatConflict(vec![
+            IdFormat::Prefixed,
+            IdFormat::Bare
+        ]))
+    );
+}
+
+// without tokio the lookups run on any executor, here the one from futures
+#[cfg(not(feature = "tokio"))]
+#[test]
+fn rpc_without_tokio() {
+    let client = FakeNodes::new(vec![
+        Ok(fake_keys(
+            "436676f1c06a11f805a92d5d02a5789296c562d1aeb8e72d6318760f61cdcbf",
+        )),
+        Err(TorusError::Timeout),
+        Ok(fake_keys(
+            "436676f1c06a11f805a92d5d02a5789296c562d1aeb8e72d6318760f61cdcbf",
+        )),
+    ])
+    .client();
+    let key = futures::executor::block_on(
+        client.lookup_request(&VerifierId::twitter(1415723267256639488)),
+    )
+    .unwrap();
+    assert!(key.is_some());
+}
+
+#[test]
+fn verifier_names() {
+    assert_eq!("Twitter".parse::<Verifier>().unwrap(), Verifier::Twitter);
+    assert_eq!(
+        "github-partisia".parse::<Verifier>().unwrap(),
+        Verifier::Custom("github-partisia".to_string())
+    );
+    assert!(matches!(
+        "  ".parse::<Verifier>(),
+        Err(TorusError::InvalidVerifier(_))
+    ));
+    assert_eq!(Verifier::Discord.to_string(), "discord");
+
+    // custom names that would read as another verifier round-trip through a prefix
+    for name in ["discord", "Twitter", "custom:apple", "github-partisia"] {
+        let custom = Verifier::Custom(name.to_string());
+        assert_eq!(custom.to_string().parse::<Verifier>().unwrap(), custom);
+    }
+    assert_eq!(
+        Verifier::Custom("discord".to_string()).to_string(),
+        "custom:discord"
+    );
+    assert!(matches!(
+        "custom:".parse::<Verifier>(),
+        Err(TorusError::InvalidVerifier(_))
+    ));
+
+    let verifiers: Vec<Verifier> = serde_json::from_str(r#"["apple", "google"]"#).unwrap();
+    assert_eq!(
+        verifiers,
+        vec![Verifier::Apple, Verifier::Custom("google".to_string())]
+    );
+    assert_eq!(
+        serde_json::to_string(&verifiers).unwrap(),
+        r#"["apple","google"]"#
+    );
+
+    let names = VerifierNames::default();
+    assert_eq!(names.name(&Verifier::Twitter), "partisia-twitter-mainnet");
+    assert_eq!(names.name(&verifiers[1]), "google");
+}
+
+#[tokio::test]
+async fn key_lookup_custom_verifier() {
+    let lookup = json!({
+        "Index": "14745a",
+        "PublicKey": {"X": "436676f1c06a11f805a92d5d02a5789296c562d1aeb8e72d6318760f61cdcbf", "Y": "afd563755d627d1ae4021d60863acca0c3bf4e5d8f5ce24c91e55ebbf5b263b0"},
+        "Threshold": 1,
+        "Verifiers": {"github-partisia": ["42", "4242"], "partisia-twitter-mainnet": ["twitter|1415723267256639488", "twitter|@partisia"]}
+    });
+    let client = FakeNodes::new(vec![Ok(lookup.clone()), Ok(lookup.clone()), Ok(lookup)]).client();
+
+    let github: Verifier = "github-partisia".parse().unwrap();
+    let id = client
+        .key_lookup_request_for(&[0; 32], &[0; 32], github.clone())
+        .await
+        .unwrap();
+    assert_eq!(id.as_ref().map(VerifierId::id), Some("4242"));
+    let id = client
+        .key_lookup_request_for(&[0; 32], &[0; 32], Verifier::Custom("google".to_string()))
+        .await
+        .unwrap();
+    assert_eq!(id, None);
+
+    let owner = |verifier: &Verifier, id: &str| VerifierId::new(verifier.clone(), id).unwrap();
+    let lookup = client.key_lookup_request(&[0; 32], &[0; 32]).await.unwrap();
+    assert_eq!(
+        lookup,
+        KeyLookup {
+            index: 0x14745a,
+            threshold: 1,
+            owners: vec![
+                owner(&github, "42"),
+                owner(&github, "4242"),
+                VerifierId::twitter(1415723267256639488),
+                // kept as the node wrote it, though it is no twitter user id
+                VerifierId::unchecked(Verifier::Twitter, "twitter|@partisia"),
+            ],
+        }
+    );
+    assert_eq!(lookup.ids(&github).count(), 2);
+    assert_eq!(lookup.owners[3].id(), "@partisia");
+}
+
+#[test]
+fn verifier_ids() {
+    let id = VerifierId::new(Verifier::Twitter, "twitter|1415723267256639488").unwrap();
+    assert_eq!(id, VerifierId::twitter(1415723267256639488));
+    assert_eq!(id.id(), "1415723267256639488");
+    assert_eq!(id.to_string(), "twitter|1415723267256639488");
+    assert_eq!(
+        VerifierId::new(Verifier::Twitter, "200985979").unwrap(),
+        VerifierId::twitter(200985979)
+    );
+    let id = VerifierId::new(Verifier::Twitter, "+0123").unwrap();
+    assert_eq!(id, VerifierId::twitter(123));
+    assert_eq!(id.to_string(), "twitter|123");
+    assert_eq!(
+        VerifierId::new(Verifier::Discord, "007").unwrap(),
+        VerifierId::discord(7)
+    );
+    assert_eq!(
+        VerifierId::discord(783831719589314610).to_string(),
+        "783831719589314610"
+    );
+    assert!(VerifierId::apple("001234.0123456789abcdef0123456789abcdef.1234").is_ok());
+
+    for (verifier, id) in [
+        (Verifier::Twitter, "twitter|@partisia"),
+        (Verifier::Discord, "twitter|783831719589314610"),
+        (Verifier::Apple, "sub with spaces"),
+        (Verifier::Custom("google".to_string()), ""),
+    ] {
+        assert!(matches!(
+            VerifierId::new(verifier, id),
+            Err(TorusError::InvalidVerifierId(_))
+        ));
+    }
+}
+
+#[test]
+fn blocking_lookup() {
+    let honest = "436676f1c06a11f805a92d5d02a5789296c562d1aeb8e72d6318760f61cdcbf";
+    let client = blocking::TorusClient::from(
+        FakeNodes::new(vec![
+            Ok(fake_keys(honest)),
+            Err(TorusError::Timeout),
+            Ok(fake_keys(honest)),
+        ])
+        .client(),
+    );
+    let consensus = client
+        .lookup_request_with_policy(
+            &VerifierId::twitter(1415723267256639488),
+            ConsensusPolicy::Majority,
+        )
+        .unwrap();
+    assert_eq!(consensus.value, Some(hex_literal::hex!("040436676f1c06a11f805a92d5d02a5789296c562d1aeb8e72d6318760f61cdcbfafd563755d627d1ae4021d60863acca0c3bf4e5d8f5ce24c91e55ebbf5b263b0")));
+    assert_eq!(consensus.required, 2);
+}
+
+#[tokio::test]
+async fn rpc_consensus_with_fake_nodes() {
+    let honest = "436676f1c06a11f805a92d5d02a5789296c562d1aeb8e72d6318760f61cdcbf";
+    let client = FakeNodes::new(vec![
+        Ok(fake_keys(OTHER_PUB_KEY_X)),
+        Ok(fake_keys(honest)),
+        Ok(fake_keys(honest)),
+        Ok(fake_keys(honest)),
+        Ok(fake_keys(honest)),
+    ])
+    .client();
+
+    let (keys, report) = client
+        .lookup_request_with_report(
+            &VerifierId::twitter(1415723267256639488),
+            ConsensusPolicy::Majority,
+        )
+        .await;
+    assert_eq!(keys.unwrap().value, Some(hex_literal::hex!("040436676f1c06a11f805a92d5d02a5789296c562d1aeb8e72d6318760f61cdcbfafd563755d627d1ae4021d60863acca0c3bf4e5d8f5ce24c91e55ebbf5b263b0")));
+    let outcomes: Vec<_> = report.nodes.into_iter().map(|n| n.outcome).collect();
+    assert_eq!(
+        outcomes,
+        vec![
+            NodeOutcome::Disagreed,
+            NodeOutcome::Agreed,
+            NodeOutcome::Agreed,
+            NodeOutcome::Agreed,
+            NodeOutcome::Cancelled,
+        ]
+    );
+}
+
+#[tokio::test]
+async fn lookup_all_keys() {
+    let mut keys = fake_keys("436676f1c06a11f805a92d5d02a5789296c562d1aeb8e72d6318760f61cdcbf");
+    let mut second = keys["keys"][0].clone();
+    second["key_index"] = json!("14745b");
+    second["pub_key_Y"] = json!("502a9c8aa29d82e51bfde29f79c5335f3c40b1a270a31db36e1aa1430a4d987f");
+    keys["keys"].as_array_mut().unwrap().push(second);
+    let client = FakeNodes::new(vec![Ok(keys.clone()), Ok(keys.clone()), Ok(keys)]).client();
+
+    let keys = client
+        .lookup_keys_request(&VerifierId::twitter(1415723267256639488))
+        .await
+        .unwrap();
+    assert_eq!(keys.keys.len(), 2);
+    let primary = keys.primary().unwrap();
+    assert_eq!(primary.index, 0x14745a);
+    assert_eq!(primary.uncompressed, hex_literal::hex!("040436676f1c06a11f805a92d5d02a5789296c562d1aeb8e72d6318760f61cdcbfafd563755d627d1ae4021d60863acca0c3bf4e5d8f5ce24c91e55ebbf5b263b0"));
+    assert_eq!(
+        primary.compressed,
+        hex_literal::hex!("020436676f1c06a11f805a92d5d02a5789296c562d1aeb8e72d6318760f61cdcbf")
+    );
+    assert_eq!(
+        primary.address,
+        "0xC9F0af3d1D6089992C0041902D846c4b448311F2"
+    );
+    assert_eq!(keys.keys[1].index, 0x14745b);
+    assert_eq!(keys.keys[1].compressed[0], 0x03);
+}
+
+#[tokio::test]
+async fn rpc_consensus_compares_canonical_form() {
+    let honest = fake_keys("436676f1c06a11f805a92d5d02a5789296c562d1aeb8e72d6318760f61cdcbf");
+    let mut padded = honest.clone();
+    padded["keys"][0]["pub_key_X"] =
+        json!("0436676F1C06A11F805A92D5D02A5789296C562D1AEB8E72D6318760F61CDCBF");
+    let mut lower_case = honest.clone();
+    lower_case["keys"][0]["address"] = json!("0xc9f0af3d1d6089992c0041902d846c4b448311f2");
+    let client = FakeNodes::new(vec![Ok(honest), Ok(padded), Ok(lower_case)]).client();
+
+    let (keys, report) = client
+        .lookup_request_with_report(
+            &VerifierId::twitter(1415723267256639488),
+            ConsensusPolicy::Unanimous,
+        )
+        .await;
+    assert!(keys.unwrap().value.is_some());
+    assert!(report
+        .nodes
+        .iter()
+        .all(|n| n.outcome == NodeOutcome::Agreed));
+
+    // a custom method whose results only have to agree on the field the caller cares about
+    let client = FakeNodes::new(vec![
+        Ok(json!({"value": 7, "served_by": "a"})),
+        Ok(json!({"value": 7, "served_by": "b"})),
+        Ok(json!({"value": 8, "served_by": "c"})),
+    ])
+    .client();
+    let consensus = client
+        .request_with_consensus(
+            "CustomRequest",
+            json!({}),
+            ConsensusPolicy::Majority,
+            |result: &Value| Ok(result["value"].as_u64()),
+        )
         .await
         .unwrap();
+    assert_eq!(consensus.value["value"], 7);
+}
+
+// a method this crate does not know about, run through the same consensus as the lookups
+struct NodeVersion;
+
+#[derive(Debug, Deserialize)]
+struct NodeVersionResponse {
+    version: String,
+}
+
+impl TorusMethod for NodeVersion {
+    const NAME: &'static str = "NodeVersionRequest";
+    type Params = ();
+    type Response = NodeVersionResponse;
+
+    fn consensus_key(response: &NodeVersionResponse) -> Result<Vec<u8>> {
+        Ok(response.version.to_lowercase().into_bytes())
+    }
+}
+
+#[tokio::test]
+async fn call_custom_method() {
+    let client = FakeNodes::new(vec![
+        Ok(json!({"version": "V1", "uptime": 10})),
+        Ok(json!({"version": "v1", "uptime": 20})),
+        Ok(json!({"version": "v2", "uptime": 30})),
+    ])
+    .client();
+    let response = client.call::<NodeVersion>(()).await.unwrap();
+    assert_eq!(response.version.to_lowercase(), "v1");
+
+    let error = client
+        .call_with_policy::<NodeVersion>((), ConsensusPolicy::Unanimous)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, TorusError::NoConsensus { .. }));
+}
+
+#[tokio::test]
+async fn rpc_rejects_invalid_public_keys() {
+    let y = "afd563755d627d1ae4021d60863acca0c3bf4e5d8f5ce24c91e55ebbf5b263b0";
+    let oversized = "1".repeat(66);
+    let invalid = [
+        ("zz", y),
+        (&oversized[..], y),
+        (&oversized[..64], y),
+        ("0", "0"),
+    ];
+    let mut errors = vec![];
+    for (x, y) in invalid {
+        let mut keys = fake_keys(x);
+        keys["keys"][0]["pub_key_Y"] = json!(y);
+        let client = FakeNodes::new(vec![Ok(keys)]).client();
+        // the key is rejected before consensus, so the only node counts as failed
+        match client
+            .lookup_request(&VerifierId::twitter(1415723267256639488))
+            .await
+        {
+            Err(TorusError::AllNodesFailed(failures)) => errors.push(failures[0].error.clone()),
+            other => panic!("expected the node to fail, got {:?}", other),
+        }
+    }
+    assert!(matches!(
+        errors[0],
+        TorusError::InvalidPublicKey(PublicKeyError::MalformedHex(_))
+    ));
+    assert_eq!(
+        errors[1..],
+        [
+            TorusError::InvalidPublicKey(PublicKeyError::Oversized { len: 66 }),
+            TorusError::InvalidPublicKey(PublicKeyError::NotOnCurve),
+            TorusError::InvalidPublicKey(PublicKeyError::Infinity),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn rpc_fake_nodes_malformed_and_failing() {
+    let client = FakeNodes::new(vec![
+        Err(TorusError::Transport("connection reset".to_string())),
+        Ok(json!({"unexpected": true})),
+        Ok(fake_keys(
+            "436676f1c06a11f805a92d5d02a5789296c562d1aeb8e72d6318760f61cdcbf",
+        )),
+    ])
+    .client()
+    .with_retry_policy(RetryPolicy::none());
+
+    let err = client
+        .lookup_request(&VerifierId::twitter(1415723267256639488))
+        .await
+        .unwrap_err();
+    match err {
+        TorusError::NoConsensus { errors, .. } => {
+            assert_eq!(
+                errors[0].error,
+                TorusError::Transport("connection reset".to_string())
+            );
+            assert!(matches!(errors[1].error, TorusError::MalformedResponse(_)));
+        }
+        e => panic!("unexpected error {}", e),
+    }
+}
+
+// what a simulated node does with every request, after its delay
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone, Copy)]
+enum Script {
+    Honest,
+    // nodes forging the same key collude
+    Forged([u8; 65]),
+    Status(u16),
+    Malformed,
+    Silent,
+}
+
+// nodes driven by a script on tokio's clock, so a paused runtime replays them deterministically
+#[cfg(feature = "tokio")]
+struct ScriptedNodes {
+    scripts: Vec<(Duration, Script)>,
+}
+
+#[cfg(feature = "tokio")]
+impl ScriptedNodes {
+    // a client for `nodes` scripted nodes, cloned for each script so thousands of scenarios do
+    // not each build a reqwest client
+    fn template(nodes: usize) -> TorusClient {
+        let endpoints = (0..nodes).map(|i| format!("sim://node-{}", i));
+        TorusClient::with_network(TorusNetwork::custom(endpoints))
+    }
+
+    fn client(self, template: &TorusClient) -> TorusClient {
+        assert_eq!(template.endpoints().len(), self.scripts.len());
+        template.clone().with_transport(self)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl TorusTransport for ScriptedNodes {
+    fn send<'a>(
+        &'a self,
+        node: usize,
+        _endpoint: &'a str,
+        request: &'a Value,
+    ) -> futures::future::BoxFuture<'a, Result<Vec<u8>>> {
+        let (delay, script) = self.scripts[node];
+        Box::pin(async move {
+            tokio::time::sleep(delay).await;
+            let result = match script {
+                Script::Honest => simulated_keys(&honest_key()),
+                Script::Forged(key) => simulated_keys(&key),
+                Script::Status(status) => {
+                    return Err(TorusError::HttpStatus {
+                        status,
+                        retry_after: None,
+                    })
+                }
+                Script::Malformed => return Ok(b"{\"result\": [".to_vec()),
+                Script::Silent => futures::future::pending().await,
+            };
+            Ok(rpc_response(request, result))
+        })
+    }
+}
+
+#[cfg(feature = "tokio")]
+fn honest_key() -> [u8; 65] {
+    hex_literal::hex!("040436676f1c06a11f805a92d5d02a5789296c562d1aeb8e72d6318760f61cdcbfafd563755d627d1ae4021d60863acca0c3bf4e5d8f5ce24c91e55ebbf5b263b0")
+}
+
+// a valid curve point, so forgeries are not rejected for being malformed
+#[cfg(feature = "tokio")]
+fn forged_key(tag: u8) -> [u8; 65] {
+    let secret = libsecp256k1::SecretKey::parse(&[tag; 32]).unwrap();
+    libsecp256k1::PublicKey::from_secret_key(&secret).serialize()
+}
+
+#[cfg(feature = "tokio")]
+fn simulated_keys(key: &[u8; 65]) -> Value {
+    json!({"keys": [{
+        "key_index": "14745a",
+        "pub_key_X": hex::encode(&key[1..33]),
+        "pub_key_Y": hex::encode(&key[33..]),
+        "address": "0xC9F0af3d1D6089992C0041902D846c4b448311F2"
+    }]})
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test(start_paused = true)]
+async fn audit_stops_at_lookup_deadline() {
+    let template = ScriptedNodes::template(3).with_deadlines(Deadlines {
+        node: Duration::from_secs(10),
+        lookup: Duration::from_secs(2),
+        ..Deadlines::default()
+    });
+    let client = ScriptedNodes {
+        scripts: vec![
+            (Duration::from_millis(10), Script::Honest),
+            (Duration::from_millis(20), Script::Honest),
+            (Duration::ZERO, Script::Silent),
+        ],
+    }
+    .client(&template);
+
+    let start = tokio::time::Instant::now();
+    let audited = client
+        .audit_lookup_request(&VerifierId::twitter(1), ConsensusPolicy::Majority)
+        .await
+        .unwrap();
+    assert_eq!(start.elapsed(), Duration::from_secs(2));
+    assert_eq!(audited.value, Some(Some(honest_key())));
+    assert_eq!(audited.report.nodes[2].outcome, NodeOutcome::Cancelled);
+    assert_eq!(audited.report.nodes[2].latency, None);
+    assert_eq!(audited.dissenting_nodes(), vec![2]);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test(start_paused = true)]
+async fn byzantine_minority_never_wins() {
+    use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+    let forgeries: Vec<[u8; 65]> = (1..=4).map(forged_key).collect();
+    // no retries, their jitter would make the timings depend on more than the seed
+    let templates: Vec<TorusClient> = (0..=9)
+        .map(|nodes| ScriptedNodes::template(nodes).with_retry_policy(RetryPolicy::none()))
+        .collect();
+    let mut rng = StdRng::seed_from_u64(0x7041_7274);
+    for scenario in 0..2000 {
+        let nodes = rng.gen_range(1..=9);
+        let faulty = rng.gen_range(0..=(nodes - 1) / 2);
+        let colluding = rng.gen_bool(0.5);
+        let mut order: Vec<usize> = (0..nodes).collect();
+        order.shuffle(&mut rng);
+
+        let mut scripts = vec![(Duration::ZERO, Script::Honest); nodes];
+        let mut live_honest = 0;
+        for (rank, &node) in order.iter().enumerate() {
+            scripts[node] = if rank < faulty {
+                // byzantine nodes mostly answer fast with a wrong key
+                let forged = forgeries[if colluding { 0 } else { rank }];
+                let delay = Duration::from_millis(rng.gen_range(0..50));
+                match rng.gen_range(0..10) {
+                    0 => (delay, Script::Silent),
+                    1 => (delay, Script::Malformed),
+                    _ => (delay, Script::Forged(forged)),
+                }
+            } else {
+                let delay = Duration::from_millis(rng.gen_range(0..5000));
+                match rng.gen_range(0..10) {
+                    0 => (delay, Script::Status(503)),
+                    1 => (delay, Script::Silent),
+                    _ => {
+                        if delay < Duration::from_millis(2500) {
+                            live_honest += 1;
+                        }
+                        (delay, Script::Honest)
+                    }
+                }
+            };
+        }
+
+        let policy = match rng.gen_range(0..4) {
+            0 => ConsensusPolicy::Network,
+            1 => ConsensusPolicy::Majority,
+            2 => ConsensusPolicy::Unanimous,
+            _ => ConsensusPolicy::KOfN(rng.gen_range(nodes / 2 + 1..=nodes)),
+        };
+        let required = policy.required(nodes / 2 + 1, nodes).unwrap();
+        let client = ScriptedNodes {
+            scripts: scripts.clone(),
+        }
+        .client(&templates[nodes]);
+
+        let outcome = client
+            .call_with_policy::<VerifierLookupRequest>(
+                client.verifier_lookup_params(&VerifierId::twitter(1), IdFormat::Prefixed),
+                policy,
+            )
+            .await;
+        match outcome {
+            Ok(consensus) => assert_eq!(
+                consensus.value.first_public_key().unwrap(),
+                Some(honest_key()),
+                "scenario {} accepted a wrong key: {:?} {:?}",
+                scenario,
+                policy,
+                scripts
+            ),
+            // enough honest nodes answering within every deadline must always agree
+            Err(e) => assert!(
+                live_honest < required,
+                "scenario {} failed with {} honest nodes live: {} {:?} {:?}",
+                scenario,
+                live_honest,
+                e,
+                policy,
+                scripts
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "mock-server")]
+#[tokio::test]
+async fn mock_network_lookup() {
+    use crate::mock::{KeyDirectory, MockNetwork, NodeFaults};
+
+    let directory = KeyDirectory::seeded(
+        7,
+        "partisia-twitter-mainnet",
+        &["twitter|1415723267256639488"],
+    );
+    let expected = directory
+        .public_key("partisia-twitter-mainnet", "twitter|1415723267256639488")
+        .unwrap();
+    let faults = vec![
+        NodeFaults {
+            wrong_key: true,
+            ..NodeFaults::default()
+        },
+        NodeFaults {
+            http_error: Some(http::StatusCode::INTERNAL_SERVER_ERROR),
+            ..NodeFaults::default()
+        },
+        NodeFaults::default(),
+        NodeFaults::default(),
+        NodeFaults::default(),
+    ];
+    let nodes = MockNetwork::start(directory, faults).await.unwrap();
+    let client = nodes.client().with_retry_policy(RetryPolicy::none());
+
+    let key = client
+        .lookup_request(&VerifierId::twitter(1415723267256639488))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(key, expected);
+
+    let x: [u8; 32] = key[1..33].try_into().unwrap();
+    let y: [u8; 32] = key[33..].try_into().unwrap();
+    let lookup = client.key_lookup_request(&x, &y).await.unwrap();
+    assert_eq!(
+        lookup.ids(&Verifier::Twitter).collect::<Vec<_>>(),
+        vec![&VerifierId::twitter(1415723267256639488)]
+    );
+
+    // every node saying the id is unassigned is an answer, not a failure
+    assert_eq!(
+        client
+            .lookup_request(&VerifierId::twitter(1))
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn rpc_fetch_consensus() {
+    let j = json!({"jsonrpc":"2.0","id":10,"method":"VerifierLookupRequest","params":{"verifier":"partisia-twitter-mainnet", "verifier_id":"twitter|1415723267256639488"}});
+    let x: TorusKeys = consensus_engine::rpc_with_consensus::<SharedConsensusResults, _>(
+        &TorusClient::default(),
+        &j,
+        ConsensusPolicy::default(),
+        &TorusKeys::consensus_key,
+    )
+    .await
+    .unwrap()
+    .value;
     assert_eq!(x.keys.len(), 1);
     assert_eq!(
         x.keys[0].pub_key_x,
@@ -99,7 +1373,7 @@ async fn rpc_fetch_consensus() {
 
 #[tokio::test]
 async fn id_lookup() {
-    let torus_key = multi_thread::lookup_request("twitter|1415723267256639488", Verifier::Twitter)
+    let torus_key = lookup_request(&VerifierId::twitter(1415723267256639488))
         .await
         .unwrap();
 
@@ -117,7 +1391,7 @@ async fn id_lookup() {
 }
 #[tokio::test]
 async fn id_lookup_old_format() {
-    let torus_key = multi_thread::lookup_request("twitter|200985979", Verifier::Twitter)
+    let torus_key = lookup_request(&VerifierId::twitter(200985979))
         .await
         .unwrap();
 
@@ -126,7 +1400,7 @@ async fn id_lookup_old_format() {
 }
 #[tokio::test]
 async fn id_lookup_discord() {
-    let torus_key = multi_thread::lookup_request("783831719589314610", Verifier::Discord)
+    let torus_key = lookup_request(&VerifierId::discord(783831719589314610))
         .await
         .unwrap();
 
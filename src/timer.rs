@@ -0,0 +1,37 @@
+// Timers for the lookups. With the tokio feature they follow tokio's clock, so tests can pause it,
+// otherwise they run on a futures-timer thread and work under any executor.
+
+use std::{future::Future, time::Duration};
+
+#[cfg(feature = "tokio")]
+pub(crate) use tokio::time::Instant;
+
+#[cfg(not(feature = "tokio"))]
+pub(crate) use std::time::Instant;
+
+#[cfg(feature = "tokio")]
+pub(crate) async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await
+}
+
+#[cfg(not(feature = "tokio"))]
+pub(crate) async fn sleep(duration: Duration) {
+    futures_timer::Delay::new(duration).await
+}
+
+/// None if `future` did not finish within `duration`
+#[cfg(feature = "tokio")]
+pub(crate) async fn timeout<F: Future>(duration: Duration, future: F) -> Option<F::Output> {
+    tokio::time::timeout(duration, future).await.ok()
+}
+
+#[cfg(not(feature = "tokio"))]
+pub(crate) async fn timeout<F: Future>(duration: Duration, future: F) -> Option<F::Output> {
+    use futures::future::{select, Either};
+
+    futures::pin_mut!(future);
+    match select(future, futures_timer::Delay::new(duration)).await {
+        Either::Left((output, _)) => Some(output),
+        Either::Right(_) => None,
+    }
+}
@@ -0,0 +1,169 @@
+use crate::Result;
+#[cfg(any(feature = "tokio", feature = "isahc"))]
+use crate::{retry_after, TorusError};
+use futures::future::BoxFuture;
+#[cfg(feature = "tokio")]
+use reqwest::{
+    header::{self, HeaderValue},
+    Client,
+};
+use serde_json::Value;
+use std::{sync::Arc, time::Duration};
+
+/// Delivers a JSON-RPC request to one of the client's nodes. The consensus engine
+/// parses and compares the returned bodies, so a transport only moves bytes.
+pub trait TorusTransport: Send + Sync {
+    /// Send `request` to node `node`, whose configured endpoint is `endpoint`, and
+    /// return the response body. Errors count against that node.
+    fn send<'a>(
+        &'a self,
+        node: usize,
+        endpoint: &'a str,
+        request: &'a Value,
+    ) -> BoxFuture<'a, Result<Vec<u8>>>;
+}
+
+// what a client sends through unless it is given a transport, reqwest if both features are on
+#[cfg(feature = "tokio")]
+pub(crate) fn default_transport(connect_timeout: Duration) -> Arc<dyn TorusTransport> {
+    Arc::new(ReqwestTransport::new(connect_timeout))
+}
+
+#[cfg(all(feature = "isahc", not(feature = "tokio")))]
+pub(crate) fn default_transport(connect_timeout: Duration) -> Arc<dyn TorusTransport> {
+    Arc::new(IsahcTransport::new(connect_timeout))
+}
+
+#[cfg(not(any(feature = "tokio", feature = "isahc")))]
+pub(crate) fn default_transport(_connect_timeout: Duration) -> Arc<dyn TorusTransport> {
+    Arc::new(NoTransport)
+}
+
+// without an http client every call fails until one is set with `TorusClient::with_transport`
+#[cfg(not(any(feature = "tokio", feature = "isahc")))]
+struct NoTransport;
+
+#[cfg(not(any(feature = "tokio", feature = "isahc")))]
+impl TorusTransport for NoTransport {
+    fn send<'a>(
+        &'a self,
+        _node: usize,
+        _endpoint: &'a str,
+        _request: &'a Value,
+    ) -> BoxFuture<'a, Result<Vec<u8>>> {
+        Box::pin(futures::future::ready(Err(crate::TorusError::Transport(
+            "no http transport, enable the tokio or isahc feature or call TorusClient::with_transport"
+                .to_string(),
+        ))))
+    }
+}
+
+/// The default transport, posting to each endpoint over https with one shared connection pool
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    http: Client,
+}
+
+#[cfg(feature = "tokio")]
+impl ReqwestTransport {
+    pub fn new(connect_timeout: Duration) -> Self {
+        let http = Client::builder()
+            .connect_timeout(connect_timeout)
+            .build()
+            .expect("failed to build the http client");
+        Self { http }
+    }
+
+    pub fn from_client(http: Client) -> Self {
+        Self { http }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl TorusTransport for ReqwestTransport {
+    fn send<'a>(
+        &'a self,
+        _node: usize,
+        endpoint: &'a str,
+        request: &'a Value,
+    ) -> BoxFuture<'a, Result<Vec<u8>>> {
+        Box::pin(async move {
+            let res = self
+                .http
+                .post(endpoint)
+                .header(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static("application/json"),
+                )
+                .json(request)
+                .send()
+                .await?;
+
+            if !res.status().is_success() {
+                let status = res.status().as_u16();
+                return Err(TorusError::HttpStatus {
+                    status,
+                    retry_after: retry_after(status, res.headers()),
+                });
+            }
+            Ok(res.bytes().await?.to_vec())
+        })
+    }
+}
+
+/// Posts through libcurl, which runs its own event loop and so works under any executor
+#[cfg(feature = "isahc")]
+#[derive(Debug, Clone)]
+pub struct IsahcTransport {
+    http: isahc::HttpClient,
+}
+
+#[cfg(feature = "isahc")]
+impl IsahcTransport {
+    pub fn new(connect_timeout: Duration) -> Self {
+        use isahc::config::Configurable;
+
+        let http = isahc::HttpClient::builder()
+            .connect_timeout(connect_timeout)
+            .build()
+            .expect("failed to build the http client");
+        Self { http }
+    }
+
+    pub fn from_client(http: isahc::HttpClient) -> Self {
+        Self { http }
+    }
+}
+
+#[cfg(feature = "isahc")]
+impl TorusTransport for IsahcTransport {
+    fn send<'a>(
+        &'a self,
+        _node: usize,
+        endpoint: &'a str,
+        request: &'a Value,
+    ) -> BoxFuture<'a, Result<Vec<u8>>> {
+        use isahc::AsyncReadResponseExt;
+
+        Box::pin(async move {
+            // the same content type the reqwest transport sends
+            let req = isahc::Request::post(endpoint)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(serde_json::to_vec(request)?)
+                .map_err(|e| TorusError::Transport(e.to_string()))?;
+            let mut res = self.http.send_async(req).await?;
+
+            if !res.status().is_success() {
+                let status = res.status().as_u16();
+                return Err(TorusError::HttpStatus {
+                    status,
+                    retry_after: retry_after(status, res.headers()),
+                });
+            }
+            res.bytes()
+                .await
+                .map_err(|e| TorusError::Transport(e.to_string()))
+        })
+    }
+}
@@ -0,0 +1,201 @@
+use crate::{Result, TorusError};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// A verifier registered with the Torus nodes. The Partisia ones are named by the network's
+/// `VerifierNames`, any other is passed to the nodes by its registered name.
+///
+/// Parses from and displays as `twitter`, `discord`, `apple` or the custom name, which is also
+/// how it is (de)serialized. A custom name that would read as another verifier, such as the stock
+/// Torus `discord` verifier, is written with a `custom:` prefix, e.g. `custom:discord`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum Verifier {
+    Twitter,
+    Discord,
+    Apple,
+    Custom(String),
+}
+
+impl fmt::Display for Verifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verifier::Twitter => f.write_str("twitter"),
+            Verifier::Discord => f.write_str("discord"),
+            Verifier::Apple => f.write_str("apple"),
+            // prefixed only when needed to parse back as the same verifier
+            Verifier::Custom(name) if name.parse().ok().as_ref() != Some(self) => {
+                write!(f, "{}{}", CUSTOM_PREFIX, name)
+            }
+            Verifier::Custom(name) => f.write_str(name),
+        }
+    }
+}
+
+const CUSTOM_PREFIX: &str = "custom:";
+
+impl FromStr for Verifier {
+    type Err = TorusError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s.trim();
+        if name.is_empty() {
+            return Err(TorusError::InvalidVerifier(s.to_string()));
+        }
+        if let Some(custom) = name.strip_prefix(CUSTOM_PREFIX) {
+            if custom.is_empty() {
+                return Err(TorusError::InvalidVerifier(s.to_string()));
+            }
+            return Ok(Verifier::Custom(custom.to_string()));
+        }
+        Ok(match name.to_ascii_lowercase().as_str() {
+            "twitter" => Verifier::Twitter,
+            "discord" => Verifier::Discord,
+            "apple" => Verifier::Apple,
+            _ => Verifier::Custom(name.to_string()),
+        })
+    }
+}
+
+impl From<Verifier> for String {
+    fn from(verifier: Verifier) -> Self {
+        verifier.to_string()
+    }
+}
+
+impl TryFrom<String> for Verifier {
+    type Error = TorusError;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+/// An account as a verifier identifies it, checked against the verifier's id format when it is
+/// created so malformed ids never reach the nodes.
+///
+/// Displays as the id the nodes store, e.g. `twitter|1415723267256639488` for Twitter.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VerifierId {
+    verifier: Verifier,
+    // without any prefix the verifier's convention adds
+    id: String,
+}
+
+const TWITTER_PREFIX: &str = "twitter|";
+
+/// How a verifier id is written for the nodes. Partisia's Twitter accounts were onboarded with
+/// either form over time, so a Twitter id may be stored under both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IdFormat {
+    /// with the verifier's prefix, e.g. `twitter|1415723267256639488`
+    Prefixed,
+    /// the id alone, e.g. `1415723267256639488`
+    Bare,
+}
+
+impl VerifierId {
+    /// A Twitter user id, stored by the nodes as `twitter|<id>`
+    pub fn twitter(user_id: u64) -> Self {
+        Self {
+            verifier: Verifier::Twitter,
+            id: user_id.to_string(),
+        }
+    }
+
+    /// A Discord user id, stored by the nodes as is
+    pub fn discord(user_id: u64) -> Self {
+        Self {
+            verifier: Verifier::Discord,
+            id: user_id.to_string(),
+        }
+    }
+
+    /// An Apple `sub` claim
+    pub fn apple(sub: &str) -> Result<Self> {
+        Self::new(Verifier::Apple, sub)
+    }
+
+    /// Checks `id` against the verifier's format. A Twitter id may be given with or without its
+    /// `twitter|` prefix, custom verifiers accept any id without whitespace.
+    pub fn new(verifier: Verifier, id: &str) -> Result<Self> {
+        let invalid = |reason: &str| {
+            Err(TorusError::InvalidVerifierId(format!(
+                "{} id {:?} {}",
+                verifier, id, reason
+            )))
+        };
+        let bare = match verifier {
+            Verifier::Twitter => id.strip_prefix(TWITTER_PREFIX).unwrap_or(id),
+            _ => id,
+        };
+        match verifier {
+            Verifier::Twitter | Verifier::Discord => {
+                let user_id = match bare.parse::<u64>() {
+                    Ok(user_id) => user_id,
+                    Err(_) => return invalid("is not a numeric user id"),
+                };
+                // the digits the nodes store, e.g. 123 for +0123
+                return Ok(Self {
+                    id: user_id.to_string(),
+                    verifier,
+                });
+            }
+            // e.g. 001234.0123456789abcdef0123456789abcdef.1234
+            Verifier::Apple => {
+                if bare.is_empty() || !bare.chars().all(|c| c.is_ascii_alphanumeric() || c == '.') {
+                    return invalid("is not an apple sub");
+                }
+            }
+            Verifier::Custom(_) => {
+                if bare.is_empty() || bare.chars().any(|c| c.is_whitespace() || c.is_control()) {
+                    return invalid("is empty or contains whitespace");
+                }
+            }
+        }
+        Ok(Self::unchecked(verifier, id))
+    }
+
+    // an id as a node reported it, taken unchecked so one odd entry does not fail a whole lookup
+    pub(crate) fn unchecked(verifier: Verifier, id: &str) -> Self {
+        let bare = match verifier {
+            Verifier::Twitter => id.strip_prefix(TWITTER_PREFIX).unwrap_or(id),
+            _ => id,
+        };
+        Self {
+            id: bare.to_string(),
+            verifier,
+        }
+    }
+
+    pub fn verifier(&self) -> &Verifier {
+        &self.verifier
+    }
+
+    /// The id without the verifier's prefix
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The formats the nodes may store this id in, the current one first
+    pub fn formats(&self) -> &'static [IdFormat] {
+        match self.verifier {
+            Verifier::Twitter => &[IdFormat::Prefixed, IdFormat::Bare],
+            _ => &[IdFormat::Bare],
+        }
+    }
+
+    /// The id written in `format`, verifiers without a prefix write both formats the same
+    pub fn format(&self, format: IdFormat) -> String {
+        match (&self.verifier, format) {
+            (Verifier::Twitter, IdFormat::Prefixed) => format!("{}{}", TWITTER_PREFIX, self.id),
+            _ => self.id.clone(),
+        }
+    }
+}
+
+impl fmt::Display for VerifierId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.format(self.formats()[0]))
+    }
+}